@@ -1,157 +1,418 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use jodiemath_rs::{cbrt, cbrt_accurate, cos, exp2, log_2, sin};
+// cargo bench -- latency      one long dependency chain, ns per call
+// cargo bench -- throughput   independent calls over a big array, GB/s
+//
+// every function and domain in accuracy::FUNCTIONS is covered, with random
+// inputs spread over each domain the same way the accuracy report samples it
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use jodiemath_rs::accuracy::{Segment, Spacing, FUNCTIONS};
+use jodiemath_rs::*;
+use rand::rngs::StdRng;
+use rand::{RngExt, SeedableRng};
 use std::hint::black_box;
+use std::time::Duration;
 
-//const N: usize = 1024;
-const N: usize = 1;
+const N: usize = 4096;
 
-fn bench_cbrt(c: &mut Criterion) {
-    c.bench_function("overhead", |b| {
-        b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = input[i];
-            }
-            for i in 0..N {
-                black_box(out[i]);
-            }
-        })
-    });
-    c.bench_function("jodie exp2", |b| {
-        b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = exp2(input[i]);
-            }
-            for i in 0..N {
-                black_box(out[i]);
-            }
-        })
-    });
-    c.bench_function("std exp2", |b| {
-        b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = input[i].exp2();
-            }
-            for i in 0..N {
-                black_box(out[i]);
-            }
-        })
-    });
-    c.bench_function("jodie cbrt", |b| {
-        b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = cbrt(input[i]);
-            }
-            for i in 0..N {
-                black_box(out[i]);
+fn random_inputs(seg: &Segment, n: usize) -> Vec<f32> {
+    let mut rng = StdRng::seed_from_u64(34);
+    (0..n)
+        .map(|_| match seg.spacing {
+            Spacing::Linear => rng.random_range(seg.start..=seg.end),
+            Spacing::Bits => {
+                // negative ranges run backwards in bits
+                let (a, b) = (seg.start.to_bits(), seg.end.to_bits());
+                f32::from_bits(rng.random_range(a.min(b)..=a.max(b)))
             }
         })
-    });
-    c.bench_function("jodie accurate cbrt", |b| {
-        b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = cbrt_accurate(input[i]);
-            }
-            for i in 0..N {
-                black_box(out[i]);
+        .collect()
+}
+
+// each call waits for the previous result, `y * 0.` can't be folded away
+// since y might be inf or NaN, so it's a real dependency
+#[inline(always)]
+fn chain(f: impl Eval, input: &[f32]) -> f32 {
+    let mut y = 0.;
+    for &x in input {
+        y = f.eval(x + y * 0.);
+    }
+    y
+}
+
+#[inline(always)]
+fn map(f: impl Eval, input: &[f32], out: &mut [f32]) {
+    for (o, &x) in out.iter_mut().zip(input) {
+        *o = f.eval(x);
+    }
+}
+
+fn segments(name: &str) -> &'static [Segment] {
+    FUNCTIONS
+        .iter()
+        .find(|f| f.name == name)
+        .unwrap_or_else(|| panic!("{name} isn't in accuracy::FUNCTIONS"))
+        .segments
+}
+
+trait Eval: Copy {
+    fn eval(self, x: f32) -> f32;
+}
+
+impl Eval for fn(f32) -> f32 {
+    #[inline(always)]
+    fn eval(self, x: f32) -> f32 {
+        self(x)
+    }
+}
+
+// the functions are passed as a type of their own with an always inlined
+// call, so they get inlined (and vectorized in throughput mode) like they
+// would be in real code. A plain fn item goes through one `Fn::call` shim
+// shared by `chain` and `map`, which LLVM stops inlining once the function
+// is as big as erfinv.
+macro_rules! inlined {
+    ($f:path) => {{
+        #[derive(Clone, Copy)]
+        struct Inlined;
+        impl Eval for Inlined {
+            #[inline(always)]
+            fn eval(self, x: f32) -> f32 {
+                $f(x)
             }
-        })
-    });
+        }
+        Inlined
+    }};
+}
+
+fn bench(c: &mut Criterion, name: &str, jodie: impl Eval, std: Option<impl Eval>) {
+    for seg in segments(name) {
+        let input = random_inputs(seg, N);
+        let mut group = c.benchmark_group(format!("latency/{name} {}", seg.name));
+        group.throughput(Throughput::Elements(N as u64));
+        group.bench_function("jodie", |b| b.iter(|| chain(jodie, black_box(&input))));
+        if let Some(std) = std {
+            group.bench_function("std", |b| b.iter(|| chain(std, black_box(&input))));
+        }
+        group.finish();
+
+        let mut out = vec![0.; N];
+        let mut group = c.benchmark_group(format!("throughput/{name} {}", seg.name));
+        group.throughput(Throughput::Bytes((N * size_of::<f32>()) as u64));
+        group.bench_with_input(BenchmarkId::from_parameter("jodie"), &input, |b, input| {
+            b.iter(|| {
+                map(jodie, black_box(input), &mut out);
+                black_box(&mut out);
+            })
+        });
+        if let Some(std) = std {
+            group.bench_with_input(BenchmarkId::from_parameter("std"), &input, |b, input| {
+                b.iter(|| {
+                    map(std, black_box(input), &mut out);
+                    black_box(&mut out);
+                })
+            });
+        }
+        group.finish();
+    }
+}
+
+const NO_STD: Option<fn(f32) -> f32> = None;
+
+// std has no π scaled versions, these are what they replace
+mod std_pi {
+    use core::f32::consts::{PI, TAU};
+    pub fn sinpi(x: f32) -> f32 {
+        (x * PI).sin()
+    }
+    pub fn cospi(x: f32) -> f32 {
+        (x * PI).cos()
+    }
+    pub fn tanpi(x: f32) -> f32 {
+        (x * PI).tan()
+    }
+    pub fn sin_turns(x: f32) -> f32 {
+        (x * TAU).sin()
+    }
+    pub fn cos_turns(x: f32) -> f32 {
+        (x * TAU).cos()
+    }
+}
+
+// the fixed powers go through powf in std
+mod std_pow {
+    pub fn pow_1_4(x: f32) -> f32 {
+        x.powf(0.25)
+    }
+    pub fn pow_2_3(x: f32) -> f32 {
+        x.powf(2. / 3.)
+    }
+    pub fn pow_m1_3(x: f32) -> f32 {
+        x.powf(-1. / 3.)
+    }
+    pub fn pow_4_3(x: f32) -> f32 {
+        x.powf(4. / 3.)
+    }
+    pub fn rootn_5(x: f32) -> f32 {
+        x.abs().powf(0.2).copysign(x)
+    }
+    pub fn rootn_100(x: f32) -> f32 {
+        x.powf(0.01)
+    }
+}
+
+// rootn at the n the accuracy report uses
+mod fixed_n {
+    use jodiemath_rs::rootn;
+    pub fn rootn_2(x: f32) -> f32 {
+        rootn(x, 2)
+    }
+    pub fn rootn_5(x: f32) -> f32 {
+        rootn(x, 5)
+    }
+    pub fn rootn_m3(x: f32) -> f32 {
+        rootn(x, -3)
+    }
+    pub fn rootn_100(x: f32) -> f32 {
+        rootn(x, 100)
+    }
+}
+
+// the two-argument functions with the partners the accuracy report uses
+mod swept {
+    use jodiemath_rs::accuracy::second_arg;
+    pub fn hypot(x: f32) -> f32 {
+        jodiemath_rs::hypot(x, second_arg(x))
+    }
+    pub fn std_hypot(x: f32) -> f32 {
+        x.hypot(second_arg(x))
+    }
+    pub fn hypot3(x: f32) -> f32 {
+        let y = second_arg(x);
+        jodiemath_rs::hypot3(x, y, second_arg(y))
+    }
+    pub fn rhypot(x: f32) -> f32 {
+        jodiemath_rs::rhypot(x, second_arg(x))
+    }
+}
+
+fn bench_all(c: &mut Criterion) {
+    let overhead = random_inputs(&segments("cbrt")[0], N);
+    let mut group = c.benchmark_group("latency/overhead");
+    group.throughput(Throughput::Elements(N as u64));
+    group.bench_function("chain", |b| b.iter(|| chain(inlined!(core::convert::identity), black_box(&overhead))));
+    group.finish();
+
+    let benched = [
+        "cbrt",
+        "cbrt_accurate",
+        "cbrt_throughput",
+        "cbrt_fast",
+        "pow_1_4",
+        "pow_2_3",
+        "pow_m1_3",
+        "pow_4_3",
+        "rootn_2",
+        "rootn_5",
+        "rootn_m3",
+        "rootn_100",
+        "exp2",
+        "log_2",
+        "sin",
+        "cos",
+        "sinpi",
+        "cospi",
+        "tanpi",
+        "sin_turns",
+        "cos_turns",
+        "sinh",
+        "cosh",
+        "tanh",
+        "asinh",
+        "acosh",
+        "atanh",
+        "expm1",
+        "exp2m1",
+        "log1p",
+        "log2p1",
+        "erf",
+        "erfc",
+        "erfinv",
+        "lgamma",
+        "gamma",
+        "digamma",
+        "hypot",
+        "hypot3",
+        "rhypot",
+        "lambert_w0",
+        "lambert_wm1",
+        "sigmoid",
+        "silu",
+        "softplus",
+        "gelu",
+        "gelu_tanh",
+    ];
+    let missing: Vec<&str> = FUNCTIONS
+        .iter()
+        .map(|f| f.name)
+        .filter(|n| !benched.contains(n))
+        .collect();
+    assert!(missing.is_empty(), "not benchmarked: {missing:?}");
+
+    bench(c, "cbrt", inlined!(cbrt), Some(inlined!(f32::cbrt)));
+    bench(c, "cbrt_accurate", inlined!(cbrt_accurate), Some(inlined!(f32::cbrt)));
+    bench(c, "cbrt_throughput", inlined!(cbrt_throughput), NO_STD);
+    bench(c, "cbrt_fast", inlined!(cbrt_fast), NO_STD);
+    bench(c, "pow_1_4", inlined!(pow_1_4), Some(inlined!(std_pow::pow_1_4)));
+    bench(c, "pow_2_3", inlined!(pow_2_3), Some(inlined!(std_pow::pow_2_3)));
+    bench(c, "pow_m1_3", inlined!(pow_m1_3), Some(inlined!(std_pow::pow_m1_3)));
+    bench(c, "pow_4_3", inlined!(pow_4_3), Some(inlined!(std_pow::pow_4_3)));
+    bench(c, "rootn_2", inlined!(fixed_n::rootn_2), Some(inlined!(f32::sqrt)));
+    bench(c, "rootn_5", inlined!(fixed_n::rootn_5), Some(inlined!(std_pow::rootn_5)));
+    bench(c, "rootn_m3", inlined!(fixed_n::rootn_m3), Some(inlined!(std_pow::pow_m1_3)));
+    bench(c, "rootn_100", inlined!(fixed_n::rootn_100), Some(inlined!(std_pow::rootn_100)));
+    bench(c, "exp2", inlined!(exp2), Some(inlined!(f32::exp2)));
+    bench(c, "log_2", inlined!(log_2), Some(inlined!(f32::log2)));
+    bench(c, "sin", inlined!(sin), Some(inlined!(f32::sin)));
+    bench(c, "cos", inlined!(cos), Some(inlined!(f32::cos)));
+    bench(c, "sinpi", inlined!(sinpi), Some(inlined!(std_pi::sinpi)));
+    bench(c, "cospi", inlined!(cospi), Some(inlined!(std_pi::cospi)));
+    bench(c, "tanpi", inlined!(tanpi), Some(inlined!(std_pi::tanpi)));
+    bench(c, "sin_turns", inlined!(sin_turns), Some(inlined!(std_pi::sin_turns)));
+    bench(c, "cos_turns", inlined!(cos_turns), Some(inlined!(std_pi::cos_turns)));
+    bench(c, "sinh", inlined!(sinh), Some(inlined!(f32::sinh)));
+    bench(c, "cosh", inlined!(cosh), Some(inlined!(f32::cosh)));
+    bench(c, "tanh", inlined!(tanh), Some(inlined!(f32::tanh)));
+    bench(c, "asinh", inlined!(asinh), Some(inlined!(f32::asinh)));
+    bench(c, "acosh", inlined!(acosh), Some(inlined!(f32::acosh)));
+    bench(c, "atanh", inlined!(atanh), Some(inlined!(f32::atanh)));
+    bench(c, "expm1", inlined!(expm1), Some(inlined!(f32::exp_m1)));
+    bench(c, "exp2m1", inlined!(exp2m1), NO_STD);
+    bench(c, "log1p", inlined!(log1p), Some(inlined!(f32::ln_1p)));
+    bench(c, "log2p1", inlined!(log2p1), NO_STD);
+    bench(c, "erf", inlined!(erf), NO_STD);
+    bench(c, "erfc", inlined!(erfc), NO_STD);
+    bench(c, "erfinv", inlined!(erfinv), NO_STD);
+    bench(c, "lgamma", inlined!(lgamma), NO_STD);
+    bench(c, "gamma", inlined!(gamma), NO_STD);
+    bench(c, "digamma", inlined!(digamma), NO_STD);
+    bench(c, "hypot", inlined!(swept::hypot), Some(inlined!(swept::std_hypot)));
+    bench(c, "hypot3", inlined!(swept::hypot3), NO_STD);
+    bench(c, "rhypot", inlined!(swept::rhypot), NO_STD);
+    bench(c, "lambert_w0", inlined!(lambert_w0), NO_STD);
+    bench(c, "lambert_wm1", inlined!(lambert_wm1), NO_STD);
+    bench(c, "sigmoid", inlined!(sigmoid), NO_STD);
+    bench(c, "silu", inlined!(silu), NO_STD);
+    bench(c, "softplus", inlined!(softplus), NO_STD);
+    bench(c, "gelu", inlined!(gelu), NO_STD);
+    bench(c, "gelu_tanh", inlined!(gelu_tanh), NO_STD);
+    bench_softmax(c);
+    bench_hypot(c);
+    bench_rootn(c);
+}
 
-    c.bench_function("std cbrt", |b| {
+// against the textbook version with std's exp and a plain sum
+fn bench_softmax(c: &mut Criterion) {
+    let input = random_inputs(&segments("sigmoid")[0], N);
+    let mut out = vec![0.; N];
+    let mut group = c.benchmark_group("throughput/softmax");
+    group.throughput(Throughput::Bytes((N * size_of::<f32>()) as u64));
+    group.bench_with_input(BenchmarkId::from_parameter("jodie"), &input, |b, input| {
         b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = input[i].cbrt();
-            }
-            for i in 0..N {
-                black_box(out[i]);
-            }
+            out.copy_from_slice(black_box(input));
+            softmax_inplace(&mut out);
+            black_box(&mut out);
         })
     });
-    c.bench_function("jodie cos", |b| {
+    group.bench_with_input(BenchmarkId::from_parameter("std"), &input, |b, input| {
         b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = cos(input[i]);
-            }
-            for i in 0..N {
-                black_box(out[i]);
-            }
+            out.copy_from_slice(black_box(input));
+            let max = out.iter().fold(f32::NEG_INFINITY, |m, &x| m.max(x));
+            out.iter_mut().for_each(|x| *x = (*x - max).exp());
+            let scale = 1. / out.iter().sum::<f32>();
+            out.iter_mut().for_each(|x| *x *= scale);
+            black_box(&mut out);
         })
     });
-    c.bench_function("std cos", |b| {
+    group.finish();
+}
+
+// pairs of components with magnitudes from 1e-30 to 1e30, where the naive
+// version over- and underflows for a few
+fn bench_hypot(c: &mut Criterion) {
+    let seg = Segment {
+        name: "[1e-30, 1e30]",
+        start: 1e-30,
+        end: 1e30,
+        spacing: Spacing::Bits,
+        loose_ulp: None,
+    };
+    let x = random_inputs(&seg, N);
+    let mut y = random_inputs(&seg, N);
+    y.reverse();
+    let mut out = vec![0.; N];
+    let mut group = c.benchmark_group("throughput/hypot");
+    group.throughput(Throughput::Bytes((N * size_of::<f32>()) as u64));
+    group.bench_function("jodie", |b| {
         b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = input[i].cos();
-            }
-            for i in 0..N {
-                black_box(out[i]);
+            for ((o, &x), &y) in out.iter_mut().zip(black_box(&x)).zip(black_box(&y)) {
+                *o = hypot(x, y);
             }
+            black_box(&mut out);
         })
     });
-    c.bench_function("jodie log_2", |b| {
+    group.bench_function("std", |b| {
         b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = log_2(input[i]);
-            }
-            for i in 0..N {
-                black_box(out[i]);
+            for ((o, &x), &y) in out.iter_mut().zip(black_box(&x)).zip(black_box(&y)) {
+                *o = x.hypot(y);
             }
+            black_box(&mut out);
         })
     });
-    c.bench_function("std log_2", |b| {
+    group.bench_function("naive", |b| {
         b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = input[i].log2();
-            }
-            for i in 0..N {
-                black_box(out[i]);
+            for ((o, &x), &y) in out.iter_mut().zip(black_box(&x)).zip(black_box(&y)) {
+                *o = (x * x + y * y).sqrt();
             }
+            black_box(&mut out);
         })
     });
-    c.bench_function("jodie sin", |b| {
+    group.finish();
+}
+
+// a 5th root against powf(0.2), over the same inputs as pow_1_4
+fn bench_rootn(c: &mut Criterion) {
+    let input = random_inputs(&segments("pow_1_4")[1], N);
+    let mut out = vec![0.; N];
+    let mut group = c.benchmark_group("throughput/rootn 5");
+    group.throughput(Throughput::Bytes((N * size_of::<f32>()) as u64));
+    group.bench_with_input(BenchmarkId::from_parameter("jodie"), &input, |b, input| {
         b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = sin(input[i]);
-            }
-            for i in 0..N {
-                black_box(out[i]);
+            for (o, &x) in out.iter_mut().zip(black_box(input)) {
+                *o = rootn(x, 5);
             }
+            black_box(&mut out);
         })
     });
-    c.bench_function("std sin", |b| {
+    group.bench_with_input(BenchmarkId::from_parameter("std"), &input, |b, input| {
         b.iter(|| {
-            let input: [f32; N] = black_box([12345.0; N]);
-            let mut out: [f32; N] = [0.0; N];
-            for i in 0..N {
-                out[i] = input[i].sin();
-            }
-            for i in 0..N {
-                black_box(out[i]);
+            for (o, &x) in out.iter_mut().zip(black_box(input)) {
+                *o = x.powf(0.2);
             }
+            black_box(&mut out);
         })
     });
+    group.finish();
+}
+
+fn config() -> Criterion {
+    Criterion::default()
+        .warm_up_time(Duration::from_millis(500))
+        .measurement_time(Duration::from_secs(2))
 }
 
-criterion_group!(benches, bench_cbrt);
+criterion_group! {
+    name = benches;
+    config = config();
+    targets = bench_all
+}
 criterion_main!(benches);
@@ -0,0 +1,1407 @@
+//! ULP error measurement over fixed domains, shared by the
+//! `accuracy-report` binary and the tests so the numbers in the readme come
+//! from the same code that checks them.
+
+use crate::*;
+
+#[derive(Clone, Copy, Debug)]
+pub enum Spacing {
+    /// evenly spaced values
+    Linear,
+    /// evenly spaced bit patterns, covers every binade of a wide range
+    /// equally, the range must not cross zero
+    Bits,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Segment {
+    pub name: &'static str,
+    pub start: f32,
+    pub end: f32,
+    pub spacing: Spacing,
+    /// replaces the function's `max_ulp` for a segment around zeros or
+    /// poles, where the error is absolute and the ulp error isn't bounded
+    pub loose_ulp: Option<u64>,
+}
+
+pub struct Function {
+    pub name: &'static str,
+    pub f: fn(f32) -> f32,
+    pub reference: fn(f64) -> f64,
+    /// the standard library's version for comparison, if it has one
+    pub std: Option<fn(f32) -> f32>,
+    /// the most ulp the docs allow, `accuracy_baseline` fails past it even
+    /// when blessing, so a bug can't become the expected value. Segments
+    /// with a `loose_ulp` are held to that instead.
+    pub max_ulp: u64,
+    pub segments: &'static [Segment],
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub samples: usize,
+    pub mean: f64,
+    pub p999: u64,
+    pub max: u64,
+}
+
+const fn seg(name: &'static str, start: f32, end: f32, spacing: Spacing) -> Segment {
+    Segment {
+        name,
+        start,
+        end,
+        spacing,
+        loose_ulp: None,
+    }
+}
+
+const fn near_zeros(seg: Segment, loose_ulp: u64) -> Segment {
+    Segment {
+        loose_ulp: Some(loose_ulp),
+        ..seg
+    }
+}
+
+const NORMAL: Segment = seg("normal", f32::MIN_POSITIVE, f32::MAX, Spacing::Bits);
+
+pub const FUNCTIONS: &[Function] = &[
+    Function {
+        name: "cbrt",
+        f: cbrt,
+        reference: f64::cbrt,
+        std: Some(f32::cbrt),
+        max_ulp: 5,
+        // x * x overflows past here
+        segments: &[
+            seg("[1, 10000]", 1., 10000., Spacing::Linear),
+            seg("[1e-18, 1e18]", 1e-18, 1e18, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "cbrt_accurate",
+        f: cbrt_accurate,
+        reference: f64::cbrt,
+        std: Some(f32::cbrt),
+        max_ulp: 1,
+        segments: &[
+            seg("[1, 10000]", 1., 10000., Spacing::Linear),
+            seg("[1e-27, 1e27]", 1e-27, 1e27, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "cbrt_throughput",
+        f: cbrt_throughput,
+        reference: f64::cbrt,
+        std: Some(f32::cbrt),
+        max_ulp: 80,
+        segments: &[
+            seg("[1, 10000]", 1., 10000., Spacing::Linear),
+            seg("[1e-9, 1e9]", 1e-9, 1e9, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "cbrt_fast",
+        f: cbrt_fast,
+        reference: f64::cbrt,
+        std: Some(f32::cbrt),
+        max_ulp: 600,
+        segments: &[
+            seg("[1, 10000]", 1., 10000., Spacing::Linear),
+            seg("[1e-27, 1e27]", 1e-27, 1e27, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "pow_1_4",
+        f: pow_1_4,
+        reference: |x| x.sqrt().sqrt(),
+        std: Some(|x| x.powf(0.25)),
+        max_ulp: 1,
+        segments: &[seg("[0, 10]", 0., 10., Spacing::Linear), NORMAL],
+    },
+    Function {
+        name: "pow_2_3",
+        f: pow_2_3,
+        reference: |x| x.cbrt() * x.cbrt(),
+        std: Some(|x| x.powf(2. / 3.)),
+        max_ulp: 1,
+        segments: &[seg("[0, 10]", 0., 10., Spacing::Linear), NORMAL],
+    },
+    Function {
+        name: "pow_m1_3",
+        f: pow_m1_3,
+        reference: |x| 1. / x.cbrt(),
+        std: Some(|x| x.powf(-1. / 3.)),
+        max_ulp: 1,
+        segments: &[seg("[0, 10]", 0., 10., Spacing::Linear), NORMAL],
+    },
+    Function {
+        name: "pow_4_3",
+        f: pow_4_3,
+        reference: |x| x * x.cbrt(),
+        std: Some(|x| x.powf(4. / 3.)),
+        max_ulp: 1,
+        // overflows past 2^96
+        segments: &[
+            seg("[0, 10]", 0., 10., Spacing::Linear),
+            seg("[1e-30, 1e28]", 1e-30, 1e28, Spacing::Bits),
+        ],
+    },
+    // rootn for a few fixed n, even, odd, negative and large
+    Function {
+        name: "rootn_2",
+        f: |x| rootn(x, 2),
+        reference: |x| x.sqrt(),
+        std: Some(|x| x.sqrt()),
+        max_ulp: 1,
+        segments: &[seg("[0, 10]", 0., 10., Spacing::Linear), NORMAL],
+    },
+    Function {
+        name: "rootn_5",
+        f: |x| rootn(x, 5),
+        reference: |x| x.abs().powf(0.2).copysign(x),
+        std: Some(|x| x.abs().powf(0.2).copysign(x)),
+        max_ulp: 1,
+        segments: &[seg("[-10, 10]", -10., 10., Spacing::Linear), NORMAL],
+    },
+    Function {
+        name: "rootn_m3",
+        f: |x| rootn(x, -3),
+        reference: |x| 1. / x.cbrt(),
+        std: Some(|x| 1. / x.cbrt()),
+        max_ulp: 1,
+        segments: &[seg("[-10, 10]", -10., 10., Spacing::Linear), NORMAL],
+    },
+    Function {
+        name: "rootn_100",
+        f: |x| rootn(x, 100),
+        reference: |x| x.powf(0.01),
+        std: Some(|x| x.powf(0.01)),
+        max_ulp: 1,
+        segments: &[seg("[0, 10]", 0., 10., Spacing::Linear), NORMAL],
+    },
+    Function {
+        name: "exp2",
+        f: exp2,
+        reference: f64::exp2,
+        std: Some(f32::exp2),
+        max_ulp: 2,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-126, 127]", -126., 127., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "log_2",
+        f: log_2,
+        reference: f64::log2,
+        std: Some(f32::log2),
+        max_ulp: 3,
+        // relative error near the zero at 1
+        segments: &[
+            near_zeros(seg("[0.2, 100]", 0.2, 100., Spacing::Linear), 1 << 17),
+            near_zeros(NORMAL, 1 << 17),
+            seg("[2, 1e30]", 2., 1e30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "sin",
+        f: sin,
+        reference: f64::sin,
+        std: Some(f32::sin),
+        max_ulp: 8,
+        // relative error near the zeros at multiples of pi
+        segments: &[
+            near_zeros(
+                seg("[-pi, pi]", -core::f32::consts::PI, core::f32::consts::PI, Spacing::Linear),
+                1 << 18,
+            ),
+            near_zeros(seg("[-1000, 1000]", -1000., 1000., Spacing::Linear), 1 << 18),
+            seg("[0.1, 3]", 0.1, 3., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "cos",
+        f: cos,
+        reference: f64::cos,
+        std: Some(f32::cos),
+        max_ulp: 6,
+        segments: &[
+            seg("[-pi, pi]", -core::f32::consts::PI, core::f32::consts::PI, Spacing::Linear),
+            seg("[-1000, 1000]", -1000., 1000., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "sinpi",
+        f: sinpi,
+        reference: sinpi_reference,
+        std: Some(|x| (x * core::f32::consts::PI).sin()),
+        max_ulp: 3,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-1000, 1000]", -1000., 1000., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "cospi",
+        f: cospi,
+        reference: cospi_reference,
+        std: Some(|x| (x * core::f32::consts::PI).cos()),
+        max_ulp: 3,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-1000, 1000]", -1000., 1000., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "tanpi",
+        f: tanpi,
+        reference: |x| sinpi_reference(x) / cospi_reference(x),
+        std: Some(|x| (x * core::f32::consts::PI).tan()),
+        max_ulp: 4,
+        segments: &[
+            seg("[-0.5, 0.5]", -0.5, 0.5, Spacing::Linear),
+            seg("[-1000, 1000]", -1000., 1000., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "sin_turns",
+        f: sin_turns,
+        reference: |x| sinpi_reference(2. * x),
+        std: Some(|x| (x * core::f32::consts::TAU).sin()),
+        max_ulp: 3,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-1000, 1000]", -1000., 1000., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "cos_turns",
+        f: cos_turns,
+        reference: |x| cospi_reference(2. * x),
+        std: Some(|x| (x * core::f32::consts::TAU).cos()),
+        max_ulp: 3,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-1000, 1000]", -1000., 1000., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "sinh",
+        f: sinh,
+        reference: f64::sinh,
+        std: Some(f32::sinh),
+        max_ulp: 2,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-89, 89]", -89., 89., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "cosh",
+        f: cosh,
+        reference: f64::cosh,
+        std: Some(f32::cosh),
+        max_ulp: 2,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-89, 89]", -89., 89., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "tanh",
+        f: tanh,
+        reference: f64::tanh,
+        std: Some(f32::tanh),
+        max_ulp: 2,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "asinh",
+        f: asinh,
+        reference: f64::asinh,
+        std: Some(f32::asinh),
+        max_ulp: 2,
+        segments: &[
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "acosh",
+        f: acosh,
+        reference: f64::acosh,
+        std: Some(f32::acosh),
+        max_ulp: 3,
+        segments: &[
+            seg("[1, 10]", 1., 10., Spacing::Linear),
+            seg("[1, 1e30]", 1., 1e30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "atanh",
+        f: atanh,
+        reference: f64::atanh,
+        std: Some(f32::atanh),
+        max_ulp: 2,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[1e-30, 1]", 1e-30, 1., Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "expm1",
+        f: expm1,
+        reference: f64::exp_m1,
+        std: Some(f32::exp_m1),
+        max_ulp: 1,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-20, 88]", -20., 88., Spacing::Linear),
+            seg("[1e-30, 1]", 1e-30, 1., Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "exp2m1",
+        f: exp2m1,
+        reference: |x| (x * core::f64::consts::LN_2).exp_m1(),
+        std: None,
+        max_ulp: 2,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[-30, 127]", -30., 127., Spacing::Linear),
+            seg("[1e-30, 1]", 1e-30, 1., Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "log1p",
+        f: log1p,
+        reference: f64::ln_1p,
+        std: Some(f32::ln_1p),
+        max_ulp: 1,
+        segments: &[
+            seg("[-0.5, 1]", -0.5, 1., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+            seg("[-1, -1e-30]", -1., -1e-30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "log2p1",
+        f: log2p1,
+        reference: |x| x.ln_1p() / core::f64::consts::LN_2,
+        std: None,
+        max_ulp: 2,
+        segments: &[
+            seg("[-0.5, 1]", -0.5, 1., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+            seg("[-1, -1e-30]", -1., -1e-30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "erf",
+        f: erf,
+        reference: erf_reference,
+        std: None,
+        max_ulp: 1,
+        segments: &[
+            seg("[-4, 4]", -4., 4., Spacing::Linear),
+            seg("[1e-30, 4]", 1e-30, 4., Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "erfc",
+        f: erfc,
+        reference: erfc_reference,
+        std: None,
+        max_ulp: 4,
+        segments: &[
+            seg("[-4, 4]", -4., 4., Spacing::Linear),
+            seg("[0.5, 10]", 0.5, 10., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "erfinv",
+        f: erfinv,
+        reference: erfinv_reference,
+        std: None,
+        max_ulp: 2,
+        segments: &[
+            seg("[-1, 1]", -1., 1., Spacing::Linear),
+            seg("[0.99, 1]", 0.99, 1., Spacing::Linear),
+            seg("[1e-30, 1]", 1e-30, 1., Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "lgamma",
+        f: lgamma,
+        reference: lgamma_reference,
+        std: None,
+        max_ulp: 4,
+        segments: &[
+            seg("[0, 10]", 0., 10., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+            // zeros between the poles, the error there is absolute
+            near_zeros(seg("[-10, 0]", -10., 0., Spacing::Linear), 1 << 20),
+        ],
+    },
+    Function {
+        name: "gamma",
+        f: gamma,
+        reference: gamma_reference,
+        std: None,
+        max_ulp: 6,
+        segments: &[
+            seg("[0, 35]", 0., 35., Spacing::Linear),
+            seg("[1e-30, 35]", 1e-30, 35., Spacing::Bits),
+            seg("[-40, 0]", -40., 0., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "digamma",
+        f: digamma,
+        reference: digamma_reference,
+        std: None,
+        max_ulp: 4,
+        segments: &[
+            seg("[0, 10]", 0., 10., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+            // the error is absolute near the zeros between the poles
+            near_zeros(seg("[-10, 0]", -10., 0., Spacing::Linear), 1 << 17),
+        ],
+    },
+    // the second and third components from `second_arg`, so the sweep over
+    // x covers their ratios too
+    Function {
+        name: "hypot",
+        f: |x| hypot(x, second_arg(x)),
+        reference: |x| x.hypot(second_arg(x as f32) as f64),
+        std: Some(|x| x.hypot(second_arg(x))),
+        max_ulp: 1,
+        segments: &[
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "hypot3",
+        f: |x| hypot3(x, second_arg(x), second_arg(second_arg(x))),
+        reference: |x| {
+            let y = second_arg(x as f32);
+            let z = second_arg(y) as f64;
+            let y = y as f64;
+            (x * x + y * y + z * z).sqrt()
+        },
+        std: None,
+        max_ulp: 1,
+        segments: &[
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "rhypot",
+        f: |x| rhypot(x, second_arg(x)),
+        reference: |x| 1. / x.hypot(second_arg(x as f32) as f64),
+        std: None,
+        max_ulp: 1,
+        segments: &[
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "lambert_w0",
+        f: lambert_w0,
+        reference: lambert_w0_reference,
+        std: None,
+        max_ulp: 1,
+        // -1/e rounded up
+        segments: &[
+            seg("[-1/e, 10]", -0.36787942, 10., Spacing::Linear),
+            seg("[-1/e, -0.3]", -0.36787942, -0.3, Spacing::Linear),
+            seg("[1e-30, 1e30]", 1e-30, 1e30, Spacing::Bits),
+            seg("[-0.3, -1e-30]", -0.3, -1e-30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "lambert_wm1",
+        f: lambert_wm1,
+        reference: lambert_wm1_reference,
+        std: None,
+        max_ulp: 1,
+        segments: &[
+            seg("[-1/e, 0]", -0.36787942, 0., Spacing::Linear),
+            seg("[-1/e, -0.3]", -0.36787942, -0.3, Spacing::Linear),
+            seg("[-0.3, -1e-30]", -0.3, -1e-30, Spacing::Bits),
+        ],
+    },
+    Function {
+        name: "sigmoid",
+        f: sigmoid,
+        reference: |x| 1. / (1. + (-x).exp()),
+        std: None,
+        max_ulp: 5,
+        segments: &[
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+            seg("[-100, 20]", -100., 20., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "silu",
+        f: silu,
+        reference: |x| x / (1. + (-x).exp()),
+        std: None,
+        max_ulp: 5,
+        segments: &[
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+            seg("[-100, 100]", -100., 100., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "softplus",
+        f: softplus,
+        reference: |x| x.max(0.) + (-x.abs()).exp().ln_1p(),
+        std: None,
+        max_ulp: 5,
+        segments: &[
+            seg("[-10, 10]", -10., 10., Spacing::Linear),
+            seg("[-100, 100]", -100., 100., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "gelu",
+        f: gelu,
+        reference: |x| 0.5 * x * erfc_reference(-x * core::f64::consts::FRAC_1_SQRT_2),
+        std: None,
+        max_ulp: 5,
+        segments: &[
+            seg("[-5, 5]", -5., 5., Spacing::Linear),
+            seg("[-20, 100]", -20., 100., Spacing::Linear),
+        ],
+    },
+    Function {
+        name: "gelu_tanh",
+        f: gelu_tanh,
+        // x sigmoid(2u), no cancellation on the negative side
+        reference: |x| {
+            let u = (2. / core::f64::consts::PI).sqrt() * (x + 0.044715 * x * x * x);
+            x / (1. + (-2. * u).exp())
+        },
+        std: None,
+        max_ulp: 5,
+        segments: &[
+            seg("[-5, 5]", -5., 5., Spacing::Linear),
+            seg("[-10, 100]", -10., 100., Spacing::Linear),
+        ],
+    },
+];
+
+/// `samples` inputs spread over the segment, both ends included.
+pub fn inputs(seg: &Segment, samples: usize) -> impl Iterator<Item = f32> + '_ {
+    let last = samples.saturating_sub(1).max(1) as f64;
+    (0..samples).map(move |i| {
+        let t = i as f64 / last;
+        match seg.spacing {
+            Spacing::Linear => (seg.start as f64 + (seg.end as f64 - seg.start as f64) * t) as f32,
+            Spacing::Bits => {
+                let (a, b) = (seg.start.to_bits() as f64, seg.end.to_bits() as f64);
+                f32::from_bits((a + (b - a) * t).round() as u32)
+            }
+        }
+    })
+}
+
+/// Distance in representable f32s between `f(x)` and the correctly rounded
+/// result.
+pub fn ulp_error(f: fn(f32) -> f32, reference: fn(f64) -> f64, x: f32) -> u64 {
+    let expected = reference(x as f64) as f32;
+    let got = f(x);
+    if expected == got || (expected.is_nan() && got.is_nan()) {
+        return 0;
+    }
+    ordered(expected).abs_diff(ordered(got))
+}
+
+/// The bits as a signed integer that counts representable f32s, so the
+/// distance across zero is right.
+pub(crate) fn ordered(v: f32) -> i64 {
+    let b = v.to_bits() as i64;
+    if b & 0x80000000 != 0 {
+        0x80000000 - b
+    } else {
+        b
+    }
+}
+
+/// Inverse of [`ordered`], both zeros come back as +0.
+pub(crate) fn from_ordered(o: i64) -> f32 {
+    if o < 0 {
+        f32::from_bits((0x80000000 - o) as u32)
+    } else {
+        f32::from_bits(o as u32)
+    }
+}
+
+pub fn measure(f: fn(f32) -> f32, reference: fn(f64) -> f64, seg: &Segment, samples: usize) -> Stats {
+    let mut errs: Vec<u64> = inputs(seg, samples).map(|x| ulp_error(f, reference, x)).collect();
+    errs.sort_unstable();
+    if errs.is_empty() {
+        return Stats::default();
+    }
+    Stats {
+        samples: errs.len(),
+        mean: errs.iter().sum::<u64>() as f64 / errs.len() as f64,
+        p999: errs[(errs.len() - 1) * 999 / 1000],
+        max: errs[errs.len() - 1],
+    }
+}
+
+pub struct Row {
+    pub function: &'static str,
+    pub segment: Segment,
+    pub jodie: Stats,
+    pub std: Option<Stats>,
+}
+
+pub fn report(samples: usize) -> Vec<Row> {
+    FUNCTIONS
+        .iter()
+        .flat_map(|func| {
+            func.segments.iter().map(move |seg| Row {
+                function: func.name,
+                segment: *seg,
+                jodie: measure(func.f, func.reference, seg, samples),
+                std: func.std.map(|s| measure(s, func.reference, seg, samples)),
+            })
+        })
+        .collect()
+}
+
+pub fn to_csv(rows: &[Row]) -> String {
+    let mut out = String::from("function,domain,start,end,samples,mean_ulp,p999_ulp,max_ulp,std_mean_ulp,std_max_ulp\n");
+    for r in rows {
+        let (std_mean, std_max) = match r.std {
+            Some(s) => (format!("{}", s.mean), format!("{}", s.max)),
+            None => (String::new(), String::new()),
+        };
+        out += &format!(
+            "{},\"{}\",{:e},{:e},{},{},{},{},{},{}\n",
+            r.function,
+            r.segment.name,
+            r.segment.start,
+            r.segment.end,
+            r.jodie.samples,
+            r.jodie.mean,
+            r.jodie.p999,
+            r.jodie.max,
+            std_mean,
+            std_max
+        );
+    }
+    out
+}
+
+pub fn to_json(rows: &[Row]) -> String {
+    let stats = |s: &Stats| {
+        format!(
+            "{{\"samples\": {}, \"mean_ulp\": {}, \"p999_ulp\": {}, \"max_ulp\": {}}}",
+            s.samples, s.mean, s.p999, s.max
+        )
+    };
+    let entries: Vec<String> = rows
+        .iter()
+        .map(|r| {
+            format!(
+                "  {{\"function\": \"{}\", \"domain\": \"{}\", \"start\": {:e}, \"end\": {:e}, \"jodie\": {}, \"std\": {}}}",
+                r.function,
+                r.segment.name,
+                r.segment.start,
+                r.segment.end,
+                stats(&r.jodie),
+                r.std.as_ref().map_or("null".into(), stats)
+            )
+        })
+        .collect();
+    format!("[\n{}\n]\n", entries.join(",\n"))
+}
+
+/// The readme's precision table.
+pub fn to_table(rows: &[Row]) -> String {
+    // as wide as the longest name, so the columns line up
+    let fw = rows.iter().map(|r| r.function.len()).chain(["function".len()]).max().unwrap();
+    let dw = rows.iter().map(|r| r.segment.name.len()).chain(["domain".len()]).max().unwrap();
+    let mut out = format!(
+        "{:>fw$} | {:<dw$} | mean ulp | 99.9% ulp | max ulp | std mean | std max\n\
+         {:->fw$}-|-{:-<dw$}-|----------|-----------|---------|----------|--------\n",
+        "function", "domain", "", ""
+    );
+    for r in rows {
+        let (std_mean, std_max) = match r.std {
+            Some(s) => (format!("{:.3}", s.mean), s.max.to_string()),
+            None => ("-".into(), "-".into()),
+        };
+        out += &format!(
+            "{:>fw$} | {:<dw$} | {:<8.3} | {:<9} | {:<7} | {:<8} | {}\n",
+            r.function, r.segment.name, r.jodie.mean, r.jodie.p999, r.jodie.max, std_mean, std_max
+        );
+    }
+    out
+}
+
+pub const README_START: &str = "<!-- accuracy-report start -->";
+pub const README_END: &str = "<!-- accuracy-report end -->";
+
+/// Replace the generated part of `readme` with `table`, None if the
+/// markers are missing.
+pub fn splice_readme(readme: &str, table: &str) -> Option<String> {
+    let start = readme.find(README_START)? + README_START.len();
+    let end = readme[start..].find(README_END)? + start;
+    Some(format!("{}\n```\n{}```\n{}", &readme[..start], table, &readme[end..]))
+}
+
+// ── Regression baseline ─────────────────────────────────────────────
+
+/// Samples per segment for the baseline, small enough for debug tests.
+pub const BASELINE_SAMPLES: usize = 100_000;
+pub const BASELINE_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/accuracy-baseline.csv");
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Baseline {
+    pub function: String,
+    pub domain: String,
+    pub mean: f64,
+    pub max: u64,
+}
+
+pub fn to_baseline(rows: &[(&str, &str, Stats)]) -> String {
+    let mut out = format!(
+        "# max/mean ulp per function and domain at {BASELINE_SAMPLES} samples, checked by\n\
+         # `cargo test accuracy_baseline`, rewrite with `BLESS=1 cargo test accuracy_baseline`\n\
+         function,domain,mean_ulp,max_ulp\n"
+    );
+    for (function, domain, stats) in rows {
+        out += &format!("{function},\"{domain}\",{},{}\n", stats.mean, stats.max);
+    }
+    out
+}
+
+pub fn parse_baseline(text: &str) -> Vec<Baseline> {
+    text.lines()
+        .filter(|l| !l.starts_with('#') && !l.starts_with("function,"))
+        .filter_map(|l| {
+            let mut parts = l.split('"');
+            let function = parts.next()?.strip_suffix(',')?;
+            let domain = parts.next()?;
+            let mut nums = parts.next()?.strip_prefix(',')?.split(',');
+            Some(Baseline {
+                function: function.into(),
+                domain: domain.into(),
+                mean: nums.next()?.parse().ok()?,
+                max: nums.next()?.parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+// ── f64 references std doesn't have ─────────────────────────────────
+
+/// erf to about 1e-15, as the reference and for `remez`. The all-positive
+/// series `e^-x^2 sum 2^n x^(2n+1) / (2n+1)!!` below 1.5, nothing cancels
+/// in it, `1 - erfc` above.
+pub fn erf_reference(x: f64) -> f64 {
+    let a = x.abs();
+    if a >= 1.5 {
+        return (1. - erfc_reference(a)).copysign(x);
+    }
+    let (mut term, mut sum, mut n) = (a, a, 0.);
+    while term > sum * 1e-17 {
+        n += 1.;
+        term *= 2. * a * a / (2. * n + 1.);
+        sum += term;
+    }
+    (sum * (-a * a).exp() * core::f64::consts::FRAC_2_SQRT_PI).copysign(x)
+}
+
+/// erfc to about 1e-15 relative, also deep in the tail. The continued
+/// fraction `e^-x^2 / sqrt(pi) / (x + (1/2) / (x + 1 / (x + (3/2) / ...)))`
+/// from 1.5 up, evaluated back to front from a depth where it has converged.
+pub fn erfc_reference(x: f64) -> f64 {
+    if x < 1.5 {
+        return if x > -1.5 { 1. - erf_reference(x) } else { 2. - erfc_reference(-x) };
+    }
+    let mut f = x;
+    for n in (1..=200).rev() {
+        f = x + n as f64 * 0.5 / f;
+    }
+    (-x * x).exp() / core::f64::consts::PI.sqrt() / f
+}
+
+/// erfinv to about 1e-15 relative. Winitzki's approximation as the start,
+/// then Newton on `erf_reference`, or on `erfc_reference` from 0.5 up where
+/// 1 - x is exact and keeps the bits near 1.
+pub fn erfinv_reference(x: f64) -> f64 {
+    let a = x.abs();
+    if a >= 1. {
+        return if a == 1. { f64::INFINITY.copysign(x) } else { f64::NAN };
+    }
+    let l = ((1. - a) * (1. + a)).ln();
+    let t = 2. / (core::f64::consts::PI * 0.147) + l / 2.;
+    let mut y = ((t * t - l / 0.147).sqrt() - t).sqrt();
+    // the start is good to 2e-3, convergence is quadratic
+    for _ in 0..6 {
+        let r = if a < 0.5 { erf_reference(y) - a } else { (1. - a) - erfc_reference(y) };
+        y -= r / (core::f64::consts::FRAC_2_SQRT_PI * (-y * y).exp());
+    }
+    y.copysign(x)
+}
+
+// ζ(k) - 1, the series around 1 below converge twice as fast with the 1s
+// taken out. Direct sum up to 30 and Euler-Maclaurin for the rest.
+const ZETA_TERMS: usize = 64;
+const ZETA_M1: [f64; ZETA_TERMS] = zeta_minus_one();
+
+const fn zeta_minus_one() -> [f64; ZETA_TERMS] {
+    const N: f64 = 30.;
+    // B2j/(2j)!
+    const B: [f64; 4] = [1. / 12., -1. / 720., 1. / 30240., -1. / 1209600.];
+    let mut z = [0.; ZETA_TERMS];
+    let mut k = 2;
+    while k < ZETA_TERMS {
+        let mut sum = 0.;
+        let mut n = 2.;
+        while n < N {
+            let (mut p, mut i) = (1., 0);
+            while i < k {
+                p /= n;
+                i += 1;
+            }
+            sum += p;
+            n += 1.;
+        }
+        // N^-k
+        let (mut p, mut i) = (1., 0);
+        while i < k {
+            p /= N;
+            i += 1;
+        }
+        let kf = k as f64;
+        let mut tail = p * N / (kf - 1.) + p / 2.;
+        // k (k+1) ... (k+2j-2) N^(-k-2j+1)
+        let mut d = kf * p / N;
+        let mut j = 0;
+        while j < 4 {
+            tail += B[j] * d;
+            d *= (kf + 2. * j as f64 + 1.) * (kf + 2. * j as f64 + 2.) / (N * N);
+            j += 1;
+        }
+        z[k] = sum + tail;
+        k += 1;
+    }
+    z
+}
+
+// lgamma(1 + t) for |t| <= 0.5
+fn lgamma1p_series(t: f64) -> f64 {
+    let mut sum = t * (1. - EULER_GAMMA) - t.ln_1p();
+    let mut p = -t;
+    for (k, z) in ZETA_M1.iter().enumerate().skip(2) {
+        p *= -t;
+        sum += p * z / k as f64;
+    }
+    sum
+}
+
+// digamma(1 + t) for |t| <= 0.5
+fn digamma1p_series(t: f64) -> f64 {
+    let mut sum = -EULER_GAMMA + t / (1. + t);
+    let mut p = -1.;
+    for z in &ZETA_M1[2..] {
+        p *= -t;
+        sum += p * z;
+    }
+    sum
+}
+
+/// sin(πx), the reduction is exact so it's relatively accurate near the
+/// integers.
+pub fn sinpi_reference(x: f64) -> f64 {
+    let r = x - x.round();
+    (core::f64::consts::PI * r).sin() * if x.round().rem_euclid(2.) == 0. { 1. } else { -1. }
+}
+
+/// cos(πx) as sin(π(1/2 - |x|)) after the same exact reduction.
+pub fn cospi_reference(x: f64) -> f64 {
+    let r = x - 2. * (x * 0.5).round();
+    sinpi_reference(0.5 - r.abs())
+}
+
+/// lgamma = ln|Γ| to about 1e-15, relative also around the zeros at 1 and
+/// 2. Series around 1 and 2 for [0, 2.5], Stirling after shifting up to 20
+/// above that, reflection below 0.
+pub fn lgamma_reference(x: f64) -> f64 {
+    if x.is_nan() || x == f64::INFINITY {
+        return x;
+    }
+    if x <= 0. && x == x.floor() {
+        return f64::INFINITY;
+    }
+    if x < 0. {
+        return (core::f64::consts::PI / sinpi_reference(x).abs()).ln() - lgamma_reference(1. - x);
+    }
+    if x < 0.5 {
+        return lgamma1p_series(x) - x.ln();
+    }
+    if x < 1.5 {
+        return lgamma1p_series(x - 1.);
+    }
+    if x < 2.5 {
+        return lgamma1p_series(x - 2.) + (x - 2.).ln_1p();
+    }
+    let (mut z, mut prod) = (x, 1.);
+    while z < 20. {
+        prod *= z;
+        z += 1.;
+    }
+    // B2j / (2j (2j - 1))
+    let b = [1. / 12., -1. / 360., 1. / 1260., -1. / 1680., 1. / 1188., -691. / 360360., 1. / 156.];
+    let y = 1. / (z * z);
+    let series = b.iter().rev().fold(0., |acc, c| acc * y + c) / z;
+    (z - 0.5) * z.ln() - z + 0.5 * (2. * core::f64::consts::PI).ln() + series - prod.ln()
+}
+
+/// Γ as ±e^lgamma, the sign from the reflection. ±inf at ±0, NaN at the
+/// negative integers.
+pub fn gamma_reference(x: f64) -> f64 {
+    if x == 0. {
+        return 1. / x;
+    }
+    if x < 0. && x == x.floor() {
+        return f64::NAN;
+    }
+    let sign = if x < 0. { sinpi_reference(x).signum() } else { 1. };
+    sign * lgamma_reference(x).exp()
+}
+
+// the Taylor coefficients of digamma around its positive root,
+// digamma^(n)(x0)/n! for n = 1.., from mpmath
+const DIGAMMA_ROOT_TAYLOR: [f64; 7] = [
+    0.9676722454476212,
+    -0.4427631689835921,
+    0.258499760955651,
+    -0.16394270544240652,
+    0.10782405069126237,
+    -0.07219956125645471,
+    0.04880428816414311,
+];
+
+/// digamma = Γ'/Γ to about 1e-15. The same splits as `lgamma_reference`,
+/// asymptotic expansion from 20 up, -1/x at ±0 and NaN at the negative
+/// integers.
+pub fn digamma_reference(x: f64) -> f64 {
+    if x.is_nan() || x == f64::INFINITY {
+        return x;
+    }
+    if x == 0. {
+        return -1. / x;
+    }
+    if x < 0. && x == x.floor() {
+        return f64::NAN;
+    }
+    if x < 0. {
+        let r = x - x.round();
+        let pi = core::f64::consts::PI;
+        return digamma_reference(1. - x) - pi / (pi * r).tan();
+    }
+    if x < 0.5 {
+        return digamma1p_series(x) - 1. / x;
+    }
+    // the series cancels to its last bits at the root
+    let d = (x - DIGAMMA_ROOT.0) - DIGAMMA_ROOT.1;
+    if d.abs() < 0.01 {
+        return DIGAMMA_ROOT_TAYLOR.iter().rev().fold(0., |acc, c| acc * d + c) * d;
+    }
+    if x < 1.5 {
+        return digamma1p_series(x - 1.);
+    }
+    if x < 2.5 {
+        return digamma1p_series(x - 2.) + 1. / (x - 1.);
+    }
+    let (mut z, mut sum) = (x, 0.);
+    while z < 20. {
+        sum += 1. / z;
+        z += 1.;
+    }
+    // B2j / 2j
+    let b = [1. / 12., -1. / 120., 1. / 252., -1. / 240., 1. / 132., -691. / 32760., 1. / 12.];
+    let y = 1. / (z * z);
+    let series = b.iter().rev().fold(0., |acc, c| acc * y + c) * y;
+    z.ln() - 0.5 / z - series - sum
+}
+
+/// Lambert W, the principal branch or the one below -1, to about 1e-15.
+/// The series in p = ±sqrt(2(ex + 1)) right at the branch point, where
+/// x + 1/e is taken with 1/e to twice f64, and Halley on w - x e^-w from
+/// the usual starting points elsewhere.
+fn lambert_w_reference(x: f64, lower: bool) -> f64 {
+    let d = (x + 0.36787944117144233) - 1.2428753672788363e-17;
+    if x.is_nan() || d < 0. || (lower && x > 0.) || (lower && x == f64::NEG_INFINITY) {
+        return f64::NAN;
+    }
+    if x == 0. {
+        return if lower { f64::NEG_INFINITY } else { x };
+    }
+    if x == f64::INFINITY {
+        return x;
+    }
+    let p = (2. * core::f64::consts::E * d).sqrt();
+    let p = if lower { -p } else { p };
+    let series = [
+        -1.,
+        1.,
+        -1. / 3.,
+        11. / 72.,
+        -43. / 540.,
+        769. / 17280.,
+        -221. / 8505.,
+        680863. / 43545600.,
+        -1963. / 204120.,
+        226287557. / 37623398400.,
+    ];
+    let s = series.iter().rev().fold(0., |acc, c| acc * p + c);
+    if p.abs() < 1e-3 {
+        return s;
+    }
+    let mut w = if x < -0.25 {
+        s
+    } else if lower {
+        let l1 = (-x).ln();
+        let l2 = (-l1).ln();
+        l1 - l2 + l2 / l1
+    } else {
+        let l = x.ln_1p();
+        l * (1. - l.ln_1p() / (2. + l))
+    };
+    for _ in 0..8 {
+        // e^-w overflows for the lower branch at tiny x, e^w doesn't
+        let f = if lower { w - x / w.exp() } else { w - x * (-w).exp() };
+        let w1 = w + 1.;
+        w -= f / (w1 - (w + 2.) * f / (2. * w1));
+    }
+    w
+}
+
+pub fn lambert_w0_reference(x: f64) -> f64 {
+    lambert_w_reference(x, false)
+}
+
+pub fn lambert_wm1_reference(x: f64) -> f64 {
+    lambert_w_reference(x, true)
+}
+
+/// The second argument paired with `x` for the two-argument functions,
+/// `x` times 2^-4 to 2^4 picked by a hash of its bits, the few binades apart
+/// the hypot tests use.
+#[inline]
+pub fn second_arg(x: f32) -> f32 {
+    let h = x.to_bits().wrapping_mul(0x9e3779b9);
+    x * f32::from_bits(((123 + (h >> 29)) << 23) | (h & 0x7fffff))
+}
+
+// ── Golden outputs ──────────────────────────────────────────────────
+
+/// Every `GOLDEN_STRIDE`th bit pattern, about a million inputs covering
+/// every binade of both signs, infinities and NaNs.
+pub const GOLDEN_STRIDE: usize = 4093;
+pub const GOLDEN_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/golden-hashes.csv");
+
+/// FNV-1a over the output bits at every golden input. NaNs are hashed as
+/// one value, their payload isn't something any target promises.
+pub fn output_hash(f: fn(f32) -> f32) -> u64 {
+    (0..=u32::MAX).step_by(GOLDEN_STRIDE).fold(0xcbf29ce484222325, |h, bits| {
+        let y = f(f32::from_bits(bits));
+        let y = if y.is_nan() { 0x7fc00000 } else { y.to_bits() };
+        y.to_le_bytes().iter().fold(h, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+    })
+}
+
+pub fn to_golden(hashes: &[(&str, u64)]) -> String {
+    let mut out = String::from(
+        "# output hash per function, checked by `cargo test golden_outputs` with and\n\
+         # without `--features deterministic`, rewrite with `BLESS=1 cargo test golden_outputs`\n\
+         function,hash\n",
+    );
+    for (function, hash) in hashes {
+        out += &format!("{function},{hash:016x}\n");
+    }
+    out
+}
+
+pub fn parse_golden(text: &str) -> Vec<(String, u64)> {
+    text.lines()
+        .filter(|l| !l.starts_with('#') && !l.starts_with("function,"))
+        .filter_map(|l| {
+            let (function, hash) = l.split_once(',')?;
+            Some((function.into(), u64::from_str_radix(hash, 16).ok()?))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sampling() {
+        let lin = seg("", -1., 1., Spacing::Linear);
+        let xs: Vec<f32> = inputs(&lin, 5).collect();
+        assert_eq!(xs, [-1., -0.5, 0., 0.5, 1.]);
+        let bits = seg("", 1., 4., Spacing::Bits);
+        let xs: Vec<f32> = inputs(&bits, 3).collect();
+        assert_eq!(xs, [1., 2., 4.]);
+    }
+
+    #[test]
+    fn ulps() {
+        let next = |x: f32| f32::from_bits(x.to_bits() + 1);
+        assert_eq!(ulp_error(|x| x, |x| x, 1.), 0);
+        assert_eq!(ulp_error(next, |x| x, 1.), 1);
+        assert_eq!(ulp_error(|_| -f32::from_bits(1), |_| f32::from_bits(1) as f64, 1.), 2);
+    }
+
+    // fails when any function got less accurate than accuracy-baseline.csv
+    // says, after an intentional change re-bless with
+    // BLESS=1 cargo test accuracy_baseline
+    #[test]
+    fn accuracy_baseline() {
+        let rows: Vec<(&str, &str, Stats)> = FUNCTIONS
+            .iter()
+            .flat_map(|func| {
+                func.segments
+                    .iter()
+                    .map(move |seg| (func.name, seg.name, measure(func.f, func.reference, seg, BASELINE_SAMPLES)))
+            })
+            .collect();
+        let ceilings = FUNCTIONS
+            .iter()
+            .flat_map(|func| func.segments.iter().map(move |seg| seg.loose_ulp.unwrap_or(func.max_ulp)));
+        let over: Vec<String> = rows
+            .iter()
+            .zip(ceilings)
+            .filter(|((_, _, stats), ceiling)| stats.max > *ceiling)
+            .map(|((f, d, stats), ceiling)| format!("{f} {d}: max {} > ceiling {ceiling}", stats.max))
+            .collect();
+        assert!(over.is_empty(), "{}", over.join("\n"));
+        if std::env::var_os("BLESS").is_some() {
+            std::fs::write(BASELINE_PATH, to_baseline(&rows)).unwrap();
+            return;
+        }
+        let text = std::fs::read_to_string(BASELINE_PATH).unwrap_or_default();
+        let baseline = parse_baseline(&text);
+        let mut failures = Vec::new();
+        for (function, domain, stats) in &rows {
+            let Some(b) = baseline.iter().find(|b| b.function == *function && b.domain == *domain) else {
+                failures.push(format!("{function} {domain}: no baseline"));
+                continue;
+            };
+            if stats.max > b.max || stats.mean > b.mean {
+                failures.push(format!(
+                    "{function} {domain}: mean {} max {} vs baseline mean {} max {}",
+                    stats.mean, stats.max, b.mean, b.max
+                ));
+            }
+        }
+        assert!(
+            failures.is_empty(),
+            "accuracy regressed, BLESS=1 cargo test accuracy_baseline if intended:\n{}",
+            failures.join("\n")
+        );
+    }
+
+    // fails when any output bit changed, on this or any other target,
+    // after an intentional change re-bless with BLESS=1 cargo test golden_outputs
+    #[test]
+    fn golden_outputs() {
+        let hashes: Vec<(&str, u64)> = FUNCTIONS.iter().map(|func| (func.name, output_hash(func.f))).collect();
+        if std::env::var_os("BLESS").is_some() {
+            std::fs::write(GOLDEN_PATH, to_golden(&hashes)).unwrap();
+            return;
+        }
+        let golden = parse_golden(&std::fs::read_to_string(GOLDEN_PATH).unwrap_or_default());
+        let failures: Vec<String> = hashes
+            .iter()
+            .filter(|(function, hash)| !golden.iter().any(|(f, h)| f == function && h == hash))
+            .map(|(function, hash)| format!("{function}: {hash:016x}"))
+            .collect();
+        assert!(
+            failures.is_empty(),
+            "outputs differ from golden-hashes.csv, BLESS=1 cargo test golden_outputs if intended:\n{}",
+            failures.join("\n")
+        );
+    }
+
+    #[test]
+    fn golden_roundtrip() {
+        let text = to_golden(&[("cbrt", 0x0123456789abcdef), ("exp2", 1)]);
+        assert_eq!(parse_golden(&text), [("cbrt".into(), 0x0123456789abcdef), ("exp2".into(), 1)]);
+    }
+
+    #[test]
+    fn erf_references() {
+        // from mpmath
+        let cases = [
+            (0.1, 0.1124629160182849),
+            (1., 0.8427007929497149),
+            (2.9, 0.9999589021219005),
+            (3., 0.9999779095030014),
+        ];
+        for (x, erf) in cases {
+            assert!((erf_reference(x) - erf).abs() < 1e-15, "erf({x})");
+        }
+        let cases = [
+            (0.5, 0.4795001221869535),
+            (2.5, 4.06952017444959e-4),
+            (3., 2.209049699858544e-5),
+            (5., 1.537459794428035e-12),
+            (10., 2.088487583762545e-45),
+            (26., 5.663192408856143e-296),
+            (-2., 1.995322265018953),
+        ];
+        for (x, erfc) in cases {
+            assert!(((erfc_reference(x) - erfc) / erfc).abs() < 1e-14, "erfc({x}) = {}", erfc_reference(x));
+        }
+        let cases = [
+            (0.1, 0.08885599049425769),
+            (0.5, 0.4769362762044699),
+            (-0.9, -1.163087153676674),
+            (0.999, 2.3267537655135246),
+            (1e-10, 8.86226925452758e-11),
+            (1. - 2f64.powi(-24), 3.832506856900711),
+        ];
+        for (x, erfinv) in cases {
+            assert!(((erfinv_reference(x) - erfinv) / erfinv).abs() < 1e-14, "erfinv({x}) = {}", erfinv_reference(x));
+        }
+        assert_eq!(erfinv_reference(-1.), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn gamma_references() {
+        // x, lgamma, gamma, digamma from mpmath, at the f64 nearest x
+        let cases = [
+            (0.1, 2.252712651734206, 9.51350769866873, -10.423754940411076),
+            (1e-8, 18.42068073818021, 99999999.42278434, -100000000.57721564),
+            (1.0000001, -5.772155829918507e-8, 0.9999999422784434, -0.5772155004081381),
+            (1.5, -0.12078223763524522, 0.886226925452758, 0.03648997397857652),
+            (2.00000005, 2.113921762037192e-8, 1.000000021139218, 0.42278436734517005),
+            (2.4, 0.21685932244884157, 1.2421693445043054, 0.6529011697005981),
+            (3., core::f64::consts::LN_2, 2., 0.9227843350984671),
+            (7.25, 7.0521854507385395, 1155.3810139199898, 1.910453526883736),
+            (30., 71.25703896716801, 8.841761993739702e30, 3.384438132685525),
+            (-0.5, 1.2655121234846454, -3.544907701811032, 0.03648997397857652),
+            (-2.5, -0.056243716497674054, -0.9453087204829419, 1.103156640645243),
+            (-7.3, -7.779101629826852, 0.000418387873013548, 4.33730730551005),
+            (1.4616321, -0.12148629053584863, 0.8856031944108895, -4.3514636958769774e-8),
+            (1.4616321325302124, -0.12148629053584953, 0.8856031944108887, -1.203605254910667e-8),
+        ];
+        let close = |a: f64, b: f64, tolerance: f64| ((a - b) / b).abs() < tolerance;
+        for (x, lgamma, gamma, digamma) in cases {
+            // the reflection cancels to lgamma(-2.5) = -0.056
+            let tolerance = if x < 0. { 1e-12 } else { 1e-14 };
+            assert!(close(lgamma_reference(x), lgamma, tolerance), "lgamma({x}) = {}", lgamma_reference(x));
+            assert!(close(gamma_reference(x), gamma, tolerance), "gamma({x}) = {}", gamma_reference(x));
+            assert!(close(digamma_reference(x), digamma, tolerance), "digamma({x}) = {}", digamma_reference(x));
+        }
+        assert!(close(lgamma_reference(1e10), 220258509288.81058, 1e-14));
+        assert!(close(digamma_reference(1e10), 23.025850929890456, 1e-14));
+        assert_eq!(gamma_reference(-0.), f64::NEG_INFINITY);
+        assert!(gamma_reference(-3.).is_nan());
+        assert_eq!(lgamma_reference(-3.), f64::INFINITY);
+    }
+
+    #[test]
+    fn lambert_references() {
+        // x, W0 and W-1 from mpmath, at the f64 nearest x
+        let w0 = [
+            (1., 0.5671432904097838),
+            (10., 1.7455280027406994),
+            (1e-3, 0.0009990014973385308),
+            (-1e-3, -0.001001001502671886),
+            (1e30, 64.90463377004612),
+            (3e38, 84.16407762377334),
+            (-0.3, -0.4894022271802149),
+            (-0.36, -0.8060843159708176),
+            (-0.3678794, -0.9995269666077006),
+            (-0.367879441171, -0.999998449325332),
+        ];
+        let wm1 = [
+            (-0.367879441171, -1.0000015506762712),
+            (-0.3678794, -1.0004731826130855),
+            (-0.36, -1.2227701339785062),
+            (-0.3, -1.7813370234216277),
+            (-0.1, -3.577152063957297),
+            (-1e-30, -73.37311031382298),
+            (-1e-300, -697.3227762954601),
+        ];
+        let close = |a: f64, b: f64| ((a - b) / b).abs() < 1e-14;
+        for (x, w) in w0 {
+            assert!(close(lambert_w0_reference(x), w), "W0({x}) = {}", lambert_w0_reference(x));
+        }
+        for (x, w) in wm1 {
+            assert!(close(lambert_wm1_reference(x), w), "W-1({x}) = {}", lambert_wm1_reference(x));
+        }
+        assert!(lambert_w0_reference(-0.37).is_nan());
+        assert!(lambert_wm1_reference(0.1).is_nan());
+        assert_eq!(lambert_wm1_reference(-0.), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn baseline_roundtrip() {
+        let stats = Stats {
+            samples: 10,
+            mean: 0.123456789,
+            p999: 3,
+            max: 4,
+        };
+        let text = to_baseline(&[("cbrt", "[1, 10000]", stats)]);
+        let parsed = parse_baseline(&text);
+        assert_eq!(
+            parsed,
+            [Baseline {
+                function: "cbrt".into(),
+                domain: "[1, 10000]".into(),
+                mean: 0.123456789,
+                max: 4
+            }]
+        );
+    }
+
+    #[test]
+    fn table_columns_line_up() {
+        let stats = Stats {
+            samples: 10,
+            mean: 0.5,
+            p999: 1,
+            max: 2,
+        };
+        let rows = [("cbrt", "[1, 10000]"), ("cbrt_throughput", "[-1e30, 1e30] log")].map(|(f, d)| Row {
+            function: f,
+            segment: seg(d, 1., 2., Spacing::Linear),
+            jodie: stats,
+            std: None,
+        });
+        let table = to_table(&rows);
+        let bars = |line: &str| line.match_indices('|').map(|(i, _)| i).collect::<Vec<_>>();
+        let first = bars(table.lines().next().unwrap());
+        assert!(table.lines().all(|l| bars(l) == first), "{table}");
+    }
+
+    #[test]
+    fn readme_splice() {
+        let readme = format!("a\n{README_START}\nold\n{README_END}\nb");
+        let new = splice_readme(&readme, "table\n").unwrap();
+        assert_eq!(new, format!("a\n{README_START}\n```\ntable\n```\n{README_END}\nb"));
+        assert_eq!(splice_readme(&new, "table\n").unwrap(), new);
+        assert!(splice_readme("no markers", "").is_none());
+    }
+}
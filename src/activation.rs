@@ -0,0 +1,224 @@
+//! Activation functions for f32 inference, and a softmax over a slice. All
+//! of them go through `exp2` with the log2(e) scale folded into the
+//! argument, the logistic ones as 1/(1 + e^-|x|) scaled by 2^64 so the
+//! negative side keeps its precision until the result underflows. No
+//! branches on the value, so the `_inplace` slice variants vectorize.
+
+use crate::doublefloat::consts::{LN_2, LOG2_E, SQRT_2};
+use crate::erf::erfc_sq;
+use crate::exp::{exp2_mul, exp_mul};
+use crate::scalar::fma;
+use crate::{log1p, sum_compensated, Df32};
+
+const TWO_64: f32 = 18446744073709551616.;
+const TWO_M64: f32 = 1. / TWO_64;
+// 2√(2/π) log2(e) and 0.044715 times that
+const GELU_K0: Df32 = Df32::from_f64(2.3022081981443248);
+const GELU_K1: Df32 = Df32::from_f64(0.1029432395800235);
+
+// 2^64 / (1 + e^-|t|) for t >= 0 and 2^64 e^-|t| / (1 + e^-|t|) below,
+// given e = 2^64 e^-|t|
+#[inline(always)]
+fn logistic_scaled(e: f32, negative: bool) -> f32 {
+    let n = if negative { e } else { TWO_64 };
+    n / fma(e, TWO_M64, 1.)
+}
+
+// x times the unscaled logistic, without overflowing the scaled one for big
+// x or multiplying inf by 0 for x = -inf
+#[inline(always)]
+fn times_logistic(x: f32, s: f32) -> f32 {
+    if x < 0. {
+        x.max(-200.) * s * TWO_M64
+    } else {
+        x * (s * TWO_M64)
+    }
+}
+
+#[inline(always)]
+fn sigmoid_scaled(x: f32) -> f32 {
+    logistic_scaled(exp_mul(-x.abs(), 0., TWO_64), x < 0.)
+}
+
+/// 1/(1 + e^-x)
+#[inline(always)]
+pub fn sigmoid(x: f32) -> f32 {
+    sigmoid_scaled(x) * TWO_M64
+}
+
+/// x sigmoid(x), also called swish.
+#[inline(always)]
+pub fn silu(x: f32) -> f32 {
+    times_logistic(x, sigmoid_scaled(x))
+}
+
+/// ln(1 + e^x), as max(x, 0) + ln(1 + e^-|x|) so neither side overflows.
+#[inline(always)]
+pub fn softplus(x: f32) -> f32 {
+    x.max(0.) + log1p(exp_mul(-x.abs(), 0., 1.))
+}
+
+/// x Φ(x) with the normal cdf Φ, as x erfc(-x/√2)/2.
+#[inline(always)]
+pub fn gelu(x: f32) -> f32 {
+    // erfc is 0 or 2 past ±20, and the square stays finite
+    let c = x.clamp(-20., 20.);
+    // z² = x²/2 exactly, z itself only feeds the smooth parts
+    let Df32(h, l) = Df32::from_mul(c, c);
+    // x/2 as the factor, -inf would make -inf * 0 = NaN
+    erfc_sq(-c / SQRT_2.0, 0.5 * h, 0.5 * l, 0.5 * x.max(-20.))
+}
+
+/// The tanh approximation of gelu, x (1 + tanh(√(2/π) (x + 0.044715 x³)))/2,
+/// rewritten as x/(1 + e^-2u) and evaluated as such.
+#[inline(always)]
+pub fn gelu_tanh(x: f32) -> f32 {
+    // 2u log2(e) = x (k0 + k1 x²), carried as a Df32 since it's an
+    // exponent, its rounding error becomes relative error of the result
+    let (k0, k1) = (GELU_K0, GELU_K1);
+    // far enough that e^-2u is 0 or inf, and the products stay finite
+    let c = x.clamp(-100., 100.);
+    let Df32(x2, x2l) = Df32::from_mul(c, c);
+    let Df32(p, pl) = Df32::from_mul(k1.0, x2);
+    let Df32(q, ql) = Df32::from_add(k0.0, p);
+    let ql = ql + fma(k1.1, x2, fma(k1.0, x2l, pl + k0.1));
+    let Df32(b, bl) = Df32::from_mul(c, q);
+    let bl = fma(c, ql, bl);
+    // 2^-|b + bl|, the tail to first order
+    let t = if b < 0. { bl } else { -bl };
+    let e = exp2_mul(-b.abs(), fma(TWO_64 * t, LN_2.0, TWO_64));
+    times_logistic(x, logistic_scaled(e, x < 0.))
+}
+
+/// Softmax of `xs` in place, e^(x - max) normalized to sum to 1. The max
+/// is subtracted first so nothing overflows, and the sum is compensated so
+/// long slices don't drift. An empty slice is left alone. With +inf
+/// among them the +inf ones split it evenly, the limit as they grow. All
+/// -inf, or a NaN anywhere, gives NaN, there's nothing to normalize.
+pub fn softmax_inplace(xs: &mut [f32]) {
+    let max = xs.iter().fold(f32::NEG_INFINITY, |m, &x| m.max(x));
+    if max == f32::INFINITY && !xs.iter().any(|x| x.is_nan()) {
+        // inf - inf would be NaN below
+        let n = xs.iter().filter(|&&x| x == max).count() as f32;
+        xs.iter_mut().for_each(|x| *x = if *x == max { 1. / n } else { 0. });
+        return;
+    }
+    // (x - max) log2(e) in one rounding. The error in the shift is the
+    // same factor on every term and cancels in the normalization.
+    let shift = -max * LOG2_E.0;
+    for x in xs.iter_mut() {
+        *x = exp2_mul(fma(*x, LOG2_E.0, shift), 1.);
+    }
+    let scale = 1. / sum_compensated(xs).to_f32();
+    for x in xs.iter_mut() {
+        *x *= scale;
+    }
+}
+
+/// `sigmoid` over a slice, in place.
+pub fn sigmoid_inplace(xs: &mut [f32]) {
+    xs.iter_mut().for_each(|x| *x = sigmoid(*x));
+}
+
+/// `silu` over a slice, in place.
+pub fn silu_inplace(xs: &mut [f32]) {
+    xs.iter_mut().for_each(|x| *x = silu(*x));
+}
+
+/// `softplus` over a slice, in place.
+pub fn softplus_inplace(xs: &mut [f32]) {
+    xs.iter_mut().for_each(|x| *x = softplus(*x));
+}
+
+/// `gelu` over a slice, in place.
+pub fn gelu_inplace(xs: &mut [f32]) {
+    xs.iter_mut().for_each(|x| *x = gelu(*x));
+}
+
+/// `gelu_tanh` over a slice, in place.
+pub fn gelu_tanh_inplace(xs: &mut [f32]) {
+    xs.iter_mut().for_each(|x| *x = gelu_tanh(*x));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_values() {
+        let inf = f32::INFINITY;
+        assert_eq!(sigmoid(0.), 0.5);
+        assert_eq!(sigmoid(inf), 1.);
+        assert_eq!(sigmoid(-inf), 0.);
+        assert_eq!(sigmoid(-110.), 0.);
+        assert!(sigmoid(-100.) > 0.);
+        for f in [silu, gelu, gelu_tanh] {
+            assert_eq!(f(inf), inf);
+            assert_eq!(f(-inf), 0.);
+            assert_eq!(f(1e30), 1e30);
+            assert_eq!(f(-0.).to_bits(), (-0f32).to_bits());
+        }
+        assert_eq!(softplus(inf), inf);
+        assert_eq!(softplus(-inf), 0.);
+        assert_eq!(softplus(1e30), 1e30);
+        assert_eq!(softplus(0.), core::f32::consts::LN_2);
+        for f in [sigmoid, silu, softplus, gelu, gelu_tanh] {
+            assert!(f(f32::NAN).is_nan());
+        }
+    }
+
+    #[test]
+    fn softmax() {
+        let mut empty: [f32; 0] = [];
+        softmax_inplace(&mut empty);
+
+        // large inputs would overflow e^x without the max subtracted
+        let mut xs = [1000., 999., 998., -1000.];
+        softmax_inplace(&mut xs);
+        let e = [1., (-1f64).exp(), (-2f64).exp()];
+        let total: f64 = e.iter().sum();
+        for i in 0..3 {
+            let expected = e[i] / total;
+            assert!((xs[i] as f64 - expected).abs() < 2e-7 * expected, "{i}: {}", xs[i]);
+        }
+        assert_eq!(xs[3], 0.);
+
+        let mut xs: Vec<f32> = (0..1000).map(|i| ((i * 37 % 101) as f32 - 50.) * 0.3).collect();
+        softmax_inplace(&mut xs);
+        let total: f64 = xs.iter().map(|&x| x as f64).sum();
+        assert!((total - 1.).abs() < 1e-6, "{total}");
+        assert!(xs.iter().all(|&x| x > 0.));
+
+        let inf = f32::INFINITY;
+        let mut xs = [1., inf, -inf, inf];
+        softmax_inplace(&mut xs);
+        assert_eq!(xs, [0., 0.5, 0., 0.5]);
+        let mut xs = [-inf, 3., -inf];
+        softmax_inplace(&mut xs);
+        assert_eq!(xs, [0., 1., 0.]);
+        for mut xs in [[-inf, -inf], [f32::NAN, inf]] {
+            softmax_inplace(&mut xs);
+            assert!(xs.iter().all(|x| x.is_nan()));
+        }
+    }
+
+    #[test]
+    fn batch_matches_scalar() {
+        let xs: Vec<f32> = (0..300).map(|i| (i as f32 - 150.) * 0.37).collect();
+        type Case = (fn(f32) -> f32, fn(&mut [f32]));
+        let cases: [Case; 5] = [
+            (sigmoid, sigmoid_inplace),
+            (silu, silu_inplace),
+            (softplus, softplus_inplace),
+            (gelu, gelu_inplace),
+            (gelu_tanh, gelu_tanh_inplace),
+        ];
+        for (f, batch) in cases {
+            let mut ys = xs.clone();
+            batch(&mut ys);
+            for (&x, &y) in xs.iter().zip(&ys) {
+                assert_eq!(f(x).to_bits(), y.to_bits(), "{x}");
+            }
+        }
+    }
+}
@@ -0,0 +1,56 @@
+//! Measure every public function over the domains in `accuracy::FUNCTIONS`.
+//!
+//! cargo run --release --bin accuracy-report -- [--csv|--json] [--samples N] [--readme [path]]
+//!
+//! Prints the table by default, `--readme` rewrites the precision table in
+//! readme.md between the accuracy-report markers.
+
+use jodiemath_rs::accuracy;
+use std::process::exit;
+
+fn usage() -> ! {
+    eprintln!("usage: accuracy-report [--csv|--json] [--samples N] [--readme [path]]");
+    exit(1)
+}
+
+fn main() {
+    let mut format = "table";
+    let mut samples = 1_000_000;
+    let mut readme = None;
+    let mut args = std::env::args().skip(1).peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--csv" => format = "csv",
+            "--json" => format = "json",
+            "--samples" => samples = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--readme" => {
+                readme = Some(match args.peek() {
+                    Some(p) if !p.starts_with("--") => args.next().unwrap(),
+                    _ => "readme.md".into(),
+                })
+            }
+            _ => usage(),
+        }
+    }
+    let rows = accuracy::report(samples);
+    let table = accuracy::to_table(&rows);
+    if let Some(path) = readme {
+        let text = std::fs::read_to_string(&path).unwrap_or_else(|e| {
+            eprintln!("couldn't read {path}: {e}");
+            exit(1)
+        });
+        let Some(new) = accuracy::splice_readme(&text, &table) else {
+            eprintln!("{path} is missing the {} / {} markers", accuracy::README_START, accuracy::README_END);
+            exit(1)
+        };
+        std::fs::write(&path, new).unwrap_or_else(|e| {
+            eprintln!("couldn't write {path}: {e}");
+            exit(1)
+        });
+    }
+    match format {
+        "csv" => print!("{}", accuracy::to_csv(&rows)),
+        "json" => print!("{}", accuracy::to_json(&rows)),
+        _ => print!("{table}"),
+    }
+}
@@ -0,0 +1,46 @@
+//! Certified worst-case error of every kernel next to the measured one.
+//!
+//! cargo run --release --bin bounds -- [name]... [--samples N]
+//!
+//! The rounding column is the rigorous part, the approximation column is
+//! each kernel's approximation bound, see `bound`.
+
+use jodiemath_rs::accuracy::{inputs, measure, FUNCTIONS};
+use jodiemath_rs::bound::{certify, KERNELS};
+use std::process::exit;
+
+fn usage() -> ! {
+    let names: Vec<&str> = KERNELS.iter().map(|k| k.name).collect();
+    eprintln!("usage: bounds [{}]... [--samples N]", names.join("|"));
+    exit(1)
+}
+
+fn main() {
+    let mut names = Vec::new();
+    let mut samples = 1_000_000;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--samples" => samples = args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            name if KERNELS.iter().any(|k| k.name == name) => names.push(arg),
+            _ => usage(),
+        }
+    }
+    println!("       function | domain        | rounding ulp | approx ulp | certified max | measured max | worst input");
+    println!("----------------|---------------|--------------|------------|---------------|--------------|------------");
+    for k in KERNELS.iter().filter(|k| names.is_empty() || names.iter().any(|n| n == k.name)) {
+        let func = FUNCTIONS.iter().find(|f| f.name == k.name).expect("kernel missing from accuracy::FUNCTIONS");
+        for seg in func.segments {
+            let cert = certify(k, func.reference, inputs(seg, samples));
+            let measured = measure(k.f, func.reference, seg, samples);
+            let certified = match cert.max_ulp {
+                u64::MAX => "inf".to_string(),
+                n => n.to_string(),
+            };
+            println!(
+                "{:>15} | {:<13} | {:<12.3e} | {:<10.3e} | {:<13} | {:<12} | {:e}",
+                k.name, seg.name, cert.rounding, cert.approximation, certified, measured.max, cert.worst
+            );
+        }
+    }
+}
@@ -0,0 +1,54 @@
+//! Latency and throughput bounds for every kernel, from its traced op graph.
+//!
+//! cargo run --release --bin dataflow -- [name]... [--path] [--json]
+//!
+//! `--path` also prints the ops along each critical path, `--json` prints
+//! the graphs for `dataflow-graph.html` to load instead of the table.
+
+use jodiemath_rs::trace::{kernels_json, trace, KERNELS, TIGER_LAKE};
+use std::process::exit;
+
+fn usage() -> ! {
+    let names: Vec<&str> = KERNELS.iter().map(|k| k.name).collect();
+    eprintln!("usage: dataflow [{}]... [--path] [--json]", names.join("|"));
+    exit(1)
+}
+
+fn main() {
+    let mut names = Vec::new();
+    let mut path = false;
+    let mut json = false;
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--path" => path = true,
+            "--json" => json = true,
+            name if KERNELS.iter().any(|k| k.name == name) => names.push(arg),
+            _ => usage(),
+        }
+    }
+    let uarch = &TIGER_LAKE;
+    let selected = KERNELS.iter().filter(|k| names.is_empty() || names.iter().any(|n| n == k.name));
+    if json {
+        print!("{}", kernels_json(selected, uarch));
+        return;
+    }
+    println!("{}, cycles per call", uarch.name);
+    println!("{:>15} | ops | uops | latency | rthroughput | bottleneck", "function");
+    println!("{:->15}-|-----|------|---------|-------------|-----------", "");
+    for k in selected {
+        let graph = trace(k.sample, k.traced);
+        let a = graph.analyze(uarch);
+        let bottleneck = match a.bottleneck.is_empty() {
+            true => "issue".to_string(),
+            false => a.bottleneck.join("+"),
+        };
+        println!(
+            "{:>15} | {:>3} | {:>4} | {:>7} | {:>11.2} | {}",
+            k.name, a.ops, a.uops, a.latency, a.throughput, bottleneck
+        );
+        if path {
+            let ops: Vec<&str> = a.critical_path.iter().map(|&i| graph.nodes[i].op.name()).collect();
+            println!("{:>15}   {}", "", ops.join(" -> "));
+        }
+    }
+}
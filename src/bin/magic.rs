@@ -0,0 +1,92 @@
+//! Search for the bit-trick seed constants over whole binades.
+//!
+//! cargo run --release --bin magic -- [cbrt|cbrt_accurate|pow_m1_3|pow_m1_4|rsqrt|rcp|sqrt]...
+//!     [--steps N] [--stride S] [--span S] [--window W] [--exhaustive] [--threads T]
+//!
+//! Each seed's error is periodic in the exponent, so one period of binades
+//! with stride 1 (the default) checks every input there is. `--steps`
+//! overrides how many refinement steps run before measuring the max error,
+//! by default the number the kernel itself uses.
+//!
+//! By default a ternary search over ±span finds the constant, which assumes
+//! the error is unimodal in it, so it's the best found. `--exhaustive` tries
+//! every combination of constants in ±span instead. Unless a span is given
+//! that's every u32 for the single constant seeds, so with stride 1 the
+//! result is optimal, and ±64 around both of cbrt_accurate's, where it's
+//! only the best in the span. On one core a u32 takes a minute or two, the
+//! pairs a few minutes.
+//!
+//! The seeds and steps are the kernels' own, from `tune::SEEDS`.
+
+use jodiemath_rs::tune::{binade_inputs, max_rel_error, scan_magic, search_magic, SEEDS};
+use std::process::exit;
+
+fn usage() -> ! {
+    let names: Vec<&str> = SEEDS.iter().map(|t| t.name).collect();
+    eprintln!(
+        "usage: magic [{}]... [--steps N] [--stride S] [--span S] [--window W] [--exhaustive] [--threads T]",
+        names.join("|")
+    );
+    exit(1)
+}
+
+fn main() {
+    let mut names = Vec::new();
+    let mut steps = None;
+    let mut stride = 1;
+    let mut span = None;
+    let mut window = 16;
+    let mut exhaustive = false;
+    let mut threads = 0;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || -> u32 { args.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()) };
+        match arg.as_str() {
+            "--steps" => steps = Some(value()),
+            "--stride" => stride = value() as usize,
+            "--span" => span = Some(value()),
+            "--window" => window = value(),
+            "--exhaustive" => exhaustive = true,
+            "--threads" => threads = value() as usize,
+            name if SEEDS.iter().any(|t| t.name == name) => names.push(arg),
+            _ => usage(),
+        }
+    }
+    for target in SEEDS.iter().filter(|t| names.is_empty() || names.iter().any(|n| n == t.name)) {
+        let steps = steps.unwrap_or(target.steps);
+        let inputs = binade_inputs(1.0, target.binades, stride);
+        let f = |x: f32, c: &[u32]| (target.f)(x, c, steps);
+        let reference = |x: f32| (target.reference)(x as f64);
+        let expected: Vec<f64> = inputs.iter().map(|&x| reference(x)).collect();
+        let threads = match threads {
+            0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            n => n,
+        };
+        let before = max_rel_error(&f, &expected, &inputs, target.consts, threads);
+        let whole = if target.consts.len() == 1 { u32::MAX } else { 1 << 6 };
+        let span = if exhaustive { span.unwrap_or(whole) } else { span.unwrap_or(1 << 22) };
+        let best = if exhaustive {
+            scan_magic(f, reference, &inputs, target.consts, span, threads)
+        } else {
+            search_magic(f, reference, &inputs, target.consts, span, window, threads)
+        };
+        let hex = |cs: &[u32]| cs.iter().map(|c| format!("0x{c:08x}")).collect::<Vec<_>>().join(", ");
+        println!(
+            "{:>14}: {} max rel err {:.4e} -> {} max rel err {:.4e} ({}, {} steps, {} inputs, {} {})",
+            target.name,
+            hex(target.consts),
+            before,
+            hex(&best.consts),
+            best.error,
+            match (exhaustive, span == u32::MAX && target.consts.len() == 1 && stride == 1) {
+                (true, true) => "optimal",
+                (true, false) => "best in span",
+                _ => "best found",
+            },
+            steps,
+            inputs.len(),
+            best.steps,
+            if exhaustive { "candidates" } else { "evaluations" },
+        );
+    }
+}
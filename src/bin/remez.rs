@@ -0,0 +1,427 @@
+//! Minimax coefficient generator, replaces the Mathematica/JS tooling.
+//!
+//! cargo run --release --bin remez -- <function> <lo> <hi> <degree> [options]
+//!
+//!   --rational <m>  p(x)/q(x) with a degree m denominator, q(0) = 1
+//!   --abs           minimize absolute error (default is relative)
+//!   --odd, --even   only odd/even powers in the numerator
+//!   --fix <k>=<v>   pin the x^k numerator coefficient to v
+//!   --center <c>    in powers of (x - c), better conditioned away from 0
+//!
+//! e.g. the sinf_poly coefficients (odd, so only half the interval):
+//!   remez sin 0 1.5707964 11 --odd --fix 1=1
+//!
+//! Coefficients are printed in ascending order as f32 bit patterns, along
+//! with the error before and after rounding them to f32.
+
+use jodiemath_rs::accuracy::{digamma_reference, erf_reference, erfc_reference, lgamma_reference};
+use jodiemath_rs::{DIGAMMA_ROOT, EULER_GAMMA};
+use std::process::exit;
+
+struct Problem<'a> {
+    f: &'a dyn Fn(f64) -> f64,
+    lo: f64,
+    hi: f64,
+    // numerator powers that are free, pinned ones are folded into f
+    powers: Vec<i32>,
+    fixed: Vec<(i32, f64)>,
+    den: usize,
+    relative: bool,
+}
+
+struct Approx {
+    // numerator coefficients by power, denominator q[0] == 1
+    p: Vec<(i32, f64)>,
+    q: Vec<f64>,
+    // levelled error from the last exchange
+    levelled: f64,
+}
+
+impl Problem<'_> {
+    fn weight(&self, x: f64) -> f64 {
+        if self.relative {
+            1. / (self.f)(x).abs()
+        } else {
+            1.
+        }
+    }
+    fn target(&self, x: f64) -> f64 {
+        (self.f)(x) - self.fixed.iter().map(|&(k, v)| v * x.powi(k)).sum::<f64>()
+    }
+    // point at t in [0, 1] on the interval, with chebyshev spacing for
+    // t = i/n, nudged off zeros of f where the relative weight blows up
+    fn node(&self, i: usize, n: usize) -> f64 {
+        let t = (std::f64::consts::PI * i as f64 / n as f64).cos();
+        let x = self.lo + (self.hi - self.lo) * (1. - t) / 2.;
+        if self.relative && (self.f)(x) == 0. {
+            let nudge = (self.hi - self.lo) * 1e-6;
+            if i == n { x - nudge } else { x + nudge }
+        } else {
+            x
+        }
+    }
+    fn unknowns(&self) -> usize {
+        self.powers.len() + self.den
+    }
+}
+
+impl Approx {
+    fn eval(&self, x: f64) -> f64 {
+        let p: f64 = self.p.iter().map(|&(k, c)| c * x.powi(k)).sum();
+        let q = self.q.iter().rev().fold(0., |acc, &c| acc * x + c);
+        p / q
+    }
+    fn error(&self, pb: &Problem, x: f64) -> f64 {
+        (self.eval(x) - (pb.f)(x)) * pb.weight(x)
+    }
+}
+
+// gaussian elimination with partial pivoting
+fn solve(mut a: Vec<Vec<f64>>, mut b: Vec<f64>) -> Option<Vec<f64>> {
+    let n = b.len();
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col] == 0. {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        for row in col + 1..n {
+            let m = a[row][col] / a[col][col];
+            let (top, bottom) = a.split_at_mut(row);
+            for (r, &c) in bottom[0][col..].iter_mut().zip(&top[col][col..]) {
+                *r -= m * c;
+            }
+            b[row] -= m * b[col];
+        }
+    }
+    let mut x = vec![0.; n];
+    for row in (0..n).rev() {
+        let s: f64 = (row + 1..n).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - s) / a[row][row];
+    }
+    Some(x)
+}
+
+// solve for the approximation levelling the error on the reference points
+// with E fixed in the denominator terms, which makes the system linear
+fn level_with(pb: &Problem, refs: &[f64], e: f64) -> Option<Approx> {
+    let mut a = Vec::new();
+    let mut b = Vec::new();
+    for (i, &x) in refs.iter().enumerate() {
+        let s = if i % 2 == 0 { 1. } else { -1. } / pb.weight(x);
+        let y = pb.target(x);
+        let mut row: Vec<f64> = pb.powers.iter().map(|&k| x.powi(k)).collect();
+        // -(y + s*E) * (q(x) - 1)
+        row.extend((1..=pb.den).map(|k| -(y + s * e) * x.powi(k as i32)));
+        row.push(-s);
+        a.push(row);
+        b.push(y);
+    }
+    let sol = solve(a, b)?;
+    let n = pb.powers.len();
+    let mut q = vec![1.];
+    q.extend(&sol[n..n + pb.den]);
+    let mut p: Vec<(i32, f64)> = pb.powers.iter().copied().zip(sol[..n].iter().copied()).collect();
+    // the pinned terms get multiplied by q too
+    for &(k, v) in &pb.fixed {
+        for (j, &qj) in q.iter().enumerate() {
+            p.push((k + j as i32, v * qj));
+        }
+    }
+    Some(Approx { p, q, levelled: sol[pb.unknowns()] })
+}
+
+// the rational case needs the E that solves its own system, found with the
+// secant method since plain fixed point iteration tends to oscillate
+fn level(pb: &Problem, refs: &[f64]) -> Option<Approx> {
+    let mut approx = level_with(pb, refs, 0.)?;
+    if pb.den > 0 {
+        let (mut e0, mut h0) = (0., approx.levelled);
+        let mut e1 = approx.levelled / 2.;
+        for _ in 0..64 {
+            approx = level_with(pb, refs, e1)?;
+            let h1 = approx.levelled - e1;
+            if h1.abs() <= e1.abs() * 1e-15 || h1 == h0 {
+                break;
+            }
+            let e2 = e1 - h1 * (e1 - e0) / (h1 - h0);
+            (e0, h0, e1) = (e1, h1, e2);
+        }
+    }
+    approx.levelled = approx.levelled.abs();
+    Some(approx)
+}
+
+// local extrema of the weighted error with alternating signs, refined by
+// golden section search
+fn extrema(pb: &Problem, approx: &Approx, count: usize) -> Vec<f64> {
+    let grid_len = 4000 * count;
+    let grid: Vec<f64> = (0..=grid_len).map(|i| pb.node(i, grid_len)).collect();
+    let err: Vec<f64> = grid.iter().map(|&x| approx.error(pb, x)).collect();
+    let mut found: Vec<(f64, f64)> = Vec::new();
+    for i in 0..=grid_len {
+        let left = if i == 0 { 0. } else { err[i - 1].abs() };
+        let right = if i == grid_len { 0. } else { err[i + 1].abs() };
+        if !(err[i].abs() >= left && err[i].abs() >= right) {
+            continue;
+        }
+        let mut x = grid[i];
+        if i > 0 && i < grid_len {
+            let (mut a, mut b) = (grid[i - 1], grid[i + 1]);
+            let g = (5f64.sqrt() - 1.) / 2.;
+            for _ in 0..60 {
+                let c = b - g * (b - a);
+                let d = a + g * (b - a);
+                if approx.error(pb, c).abs() > approx.error(pb, d).abs() {
+                    b = d;
+                } else {
+                    a = c;
+                }
+            }
+            x = (a + b) / 2.;
+        }
+        let e = approx.error(pb, x);
+        match found.last_mut() {
+            Some(last) if (last.1 < 0.) == (e < 0.) => {
+                if e.abs() > last.1.abs() {
+                    *last = (x, e);
+                }
+            }
+            _ => found.push((x, e)),
+        }
+    }
+    while found.len() > count {
+        if found[0].1.abs() < found[found.len() - 1].1.abs() {
+            found.remove(0);
+        } else {
+            found.pop();
+        }
+    }
+    found.into_iter().map(|(x, _)| x).collect()
+}
+
+fn max_error(pb: &Problem, approx: &Approx) -> f64 {
+    let n = 100_000;
+    (0..=n)
+        .map(|i| pb.node(i, n))
+        .map(|x| approx.error(pb, x).abs())
+        .filter(|e| e.is_finite())
+        .fold(0., f64::max)
+}
+
+fn remez(pb: &Problem) -> Option<Approx> {
+    let count = pb.unknowns() + 1;
+    let mut refs: Vec<f64> = (0..count).map(|i| pb.node(i, count - 1)).collect();
+    let mut approx = level(pb, &refs)?;
+    for _ in 0..100 {
+        let new_refs = extrema(pb, &approx, count);
+        if new_refs.len() < count {
+            break;
+        }
+        refs = new_refs;
+        approx = level(pb, &refs)?;
+        if max_error(pb, &approx) <= approx.levelled * (1. + 1e-9) {
+            break;
+        }
+    }
+    Some(approx)
+}
+
+fn function(name: &str) -> Option<fn(f64) -> f64> {
+    Some(match name {
+        "exp2" => f64::exp2,
+        "exp" => f64::exp,
+        "exp2m1" => |x| (x * std::f64::consts::LN_2).exp_m1(),
+        "expm1" => f64::exp_m1,
+        "log2" => f64::log2,
+        "ln" => f64::ln,
+        "log2p1" => |x| x.ln_1p() / std::f64::consts::LN_2,
+        "log1p" => f64::ln_1p,
+        "sin" => f64::sin,
+        "cos" => f64::cos,
+        "tan" => f64::tan,
+        "atan" => f64::atan,
+        "sinh" => f64::sinh,
+        "cosh" => f64::cosh,
+        "tanh" => f64::tanh,
+        "asinh" => f64::asinh,
+        "atanh" => f64::atanh,
+        "cbrt" => f64::cbrt,
+        "sqrt" => f64::sqrt,
+        "rsqrt" => |x| 1. / x.sqrt(),
+        "erf" => erf_reference,
+        // scaled so the tail is smooth, erfc(x) = e^-x^2 erfcx(x)
+        "erfcx" => |x| erfc_reference(x) * (x * x).exp(),
+        // 1/Γ(x) = 1 + (x - 1)(x - 2) h(x), h is entire
+        "rgamma_h" => |x| match x {
+            1. => -EULER_GAMMA,
+            2. => EULER_GAMMA - 1.,
+            _ => (-lgamma_reference(x)).exp_m1() / ((x - 1.) * (x - 2.)),
+        },
+        // lgamma(x) = (x - 1/2) ln x - x + ln(2π)/2 + s(t)/x with t = 1/x², s(0) = 1/12
+        "lgamma_asym" => |t| {
+            if t < 1e-3 {
+                return 1. / 12. + t * (-1. / 360. + t * (1. / 1260. + t * (-1. / 1680. + t / 1188.)));
+            }
+            let x = 1. / t.sqrt();
+            let ln_sqrt_2pi = 0.9189385332046728;
+            (lgamma_reference(x) - (x - 0.5) * x.ln() + x - ln_sqrt_2pi) * x
+        },
+        // digamma(x) = ln x - 1/2x - t s(t) with t = 1/x², s(0) = 1/12
+        "digamma_asym" => |t| {
+            if t < 1e-3 {
+                // the subtraction cancels, the series is exact enough here
+                return 1. / 12. + t * (-1. / 120. + t * (1. / 252. + t * (-1. / 240. + t / 132.)));
+            }
+            let x = 1. / t.sqrt();
+            (x.ln() - 0.5 / x - digamma_reference(x)) / t
+        },
+        // digamma(x) = (x - x0) r(x) around the positive root x0
+        "digamma_r" => |x| digamma_reference(x) / ((x - DIGAMMA_ROOT.0) - DIGAMMA_ROOT.1),
+        _ => return None,
+    })
+}
+
+fn usage() -> ! {
+    eprintln!(
+        "usage: remez <function> <lo> <hi> <degree> [--rational <m>] [--abs] [--odd|--even] [--fix <k>=<v>]... [--center <c>]"
+    );
+    exit(1)
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.len() < 4 {
+        usage();
+    }
+    let name = &args[0];
+    let Some(f) = function(name) else {
+        eprintln!("unknown function {name}");
+        exit(1)
+    };
+    let num = |s: &str| s.parse::<f64>().unwrap_or_else(|_| usage());
+    let (lo, hi) = (num(&args[1]), num(&args[2]));
+    let degree: i32 = args[3].parse().unwrap_or_else(|_| usage());
+    let mut den = 0;
+    let mut relative = true;
+    let mut parity = None;
+    let mut fixed = Vec::new();
+    let mut center = 0.;
+    let mut rest = args[4..].iter();
+    while let Some(arg) = rest.next() {
+        match arg.as_str() {
+            "--rational" => den = rest.next().and_then(|s| s.parse().ok()).unwrap_or_else(|| usage()),
+            "--abs" => relative = false,
+            "--odd" => parity = Some(1),
+            "--even" => parity = Some(0),
+            "--center" => center = rest.next().map(|s| num(s)).unwrap_or_else(|| usage()),
+            "--fix" => {
+                let (k, v) = rest.next().and_then(|s| s.split_once('=')).unwrap_or_else(|| usage());
+                fixed.push((k.parse().unwrap_or_else(|_| usage()), num(v)));
+            }
+            _ => usage(),
+        }
+    }
+    let powers = (0..=degree)
+        .filter(|k| parity.is_none_or(|p| k % 2 == p))
+        .filter(|k| fixed.iter().all(|&(j, _)| j != *k))
+        .collect();
+    let shifted = |t: f64| f(t + center);
+    let (lo, hi) = (lo - center, hi - center);
+    let pb = Problem { f: &shifted, lo, hi, powers, fixed, den, relative };
+    let Some(approx) = remez(&pb) else {
+        eprintln!("singular system, try a different degree or interval");
+        exit(1)
+    };
+
+    let kind = if relative { "relative" } else { "absolute" };
+    let var = if center == 0. { "x".to_string() } else { format!("x - {center}") };
+    println!(
+        "// {name} on [{}, {}] in powers of {var}, {kind} error {:.3e}",
+        lo + center,
+        hi + center,
+        max_error(&pb, &approx)
+    );
+    let p32 = print_coeffs("p", &approx.p);
+    let q: Vec<(i32, f64)> = approx.q.iter().enumerate().map(|(k, &c)| (k as i32, c)).collect();
+    let q32 = if den > 0 { print_coeffs("q", &q) } else { vec![(0, 1.)] };
+    let rounded = Approx {
+        p: p32,
+        q: q32.into_iter().map(|(_, c)| c).collect(),
+        levelled: 0.,
+    };
+    println!("// {kind} error with f32 coefficients {:.3e}", max_error(&pb, &rounded));
+}
+
+// print as an ascending array ready for `poly::horner`/`poly::estrin`,
+// returns the f32-rounded coefficients
+fn print_coeffs(name: &str, c: &[(i32, f64)]) -> Vec<(i32, f64)> {
+    let degree = c.iter().map(|&(k, _)| k).max().unwrap_or(0);
+    let mut dense = vec![0f64; degree as usize + 1];
+    for &(k, v) in c {
+        dense[k as usize] += v;
+    }
+    println!("let {name} = [");
+    for v in &dense {
+        let v = *v as f32;
+        println!("    f32::from_bits(0x{:08x}), // {v:e}", v.to_bits());
+    }
+    println!("];");
+    dense
+        .into_iter()
+        .enumerate()
+        .map(|(k, v)| (k as i32, v as f32 as f64))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chebyshev() {
+        // the best degree 3 fit to x^4 on [-1, 1] is off by T4(x)/8
+        let f = |x: f64| x.powi(4);
+        let pb = Problem {
+            f: &f,
+            lo: -1.,
+            hi: 1.,
+            powers: (0..=3).collect(),
+            fixed: vec![],
+            den: 0,
+            relative: false,
+        };
+        let approx = remez(&pb).unwrap();
+        assert!((approx.levelled - 0.125).abs() < 1e-9);
+        assert!((max_error(&pb, &approx) - 0.125).abs() < 1e-9);
+    }
+
+    #[test]
+    fn rational_beats_polynomial() {
+        let f = f64::log2;
+        let poly = Problem {
+            f: &f,
+            lo: 1.,
+            hi: 2.,
+            powers: (0..=6).collect(),
+            fixed: vec![],
+            den: 0,
+            relative: false,
+        };
+        let rational = Problem {
+            f: &f,
+            lo: 1.,
+            hi: 2.,
+            powers: (0..=3).collect(),
+            fixed: vec![],
+            den: 3,
+            relative: false,
+        };
+        let p = remez(&poly).unwrap();
+        let r = remez(&rational).unwrap();
+        let (pe, re) = (max_error(&poly, &p), max_error(&rational, &r));
+        assert!(re < pe && re < 1e-7, "{re} {pe}");
+        assert!((re - r.levelled).abs() < re * 1e-3);
+    }
+}
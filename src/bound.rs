@@ -0,0 +1,627 @@
+//! Worst-case error certificates, from running the kernels over [`Tracked`].
+//!
+//! A [`Tracked`] carries the computed f32, an f64 shadow of the ideal value
+//! the same ops would give in exact arithmetic, and a rigorous bound on the
+//! distance between the two. Every op whose f32 result isn't exact adds
+//! `u|v|/(1-u)` (plus the underflow term) on top of the propagated input
+//! errors, the standard γ₁ bound, so the bound is found per input rather
+//! than assumed per op count. Exact ops, checked in f64, add nothing, and
+//! the error-free transforms (`two_sum`, `two_prod`, `quick_two_sum` when
+//! its precondition holds) are marked exact: the pair's head carries the
+//! propagated error and the tail carries none. `two_mul_add`, the range
+//! reduction's pair, puts whatever the pair misses `a * b + c` by on the head
+//! too, so the reduction's compensation is seen.
+//!
+//! Bit casts, `floor` and `round` start over: the ideal becomes the computed
+//! value with no error. That's what the seed tricks mean, the bits only pick
+//! a starting point the later steps correct, and exp2's exponent is exact
+//! given the rounded `x + 383`. Sign copies go through [`Float::mulsign`],
+//! which keeps the error instead of starting over.
+//!
+//! The total error is the rounding bound plus a bound on the approximation
+//! error `|ideal - f(x)|`, what the kernel would be off by in exact
+//! arithmetic. Those come from the error curve of each kernel's polynomial
+//! over its reduced argument, or of its iteration over a period of seeds,
+//! taken over every f32 there (the constants below, re-derived by
+//! `approximation_exhaustive`), plus the error of the Df32 π in sin and cos.
+//! None of it looks at the input being certified, so a certificate that
+//! covers the measured error is a real check. The rounding part is loose
+//! wherever a kernel relies on cancellation the bound can't see, like Newton
+//! steps or hand-written compensation, the certificate is a ceiling, not an
+//! estimate.
+
+use crate::accuracy::ordered;
+use crate::doublefloat::consts;
+use crate::kernels;
+use crate::scalar::{Bits, Float};
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Shl, Shr, Sub};
+
+// unit roundoff and the absolute error of a product or fma that underflows
+const U: f64 = 1. / (1u64 << 24) as f64;
+const ETA: f64 = f32::MIN_POSITIVE as f64 / (1u64 << 24) as f64;
+// the f64 shadow rounds too, far below anything an f32 ulp can see
+const SHADOW: f64 = 1e-12;
+
+// max |p(f)/2^f - 1| over f in [0, 1), exp2's polynomial
+const EXP2_POLY: f64 = 1.43e-8;
+// max |r(m) - log2(m)| over m in [1, 2), log_2's rational, not 0 at 1
+const LOG2_RATIONAL: f64 = 4.44e-8;
+// max |p(z) - sin(z)| / |z| over [-pi/2, pi/2], the sin and cos polynomial
+const SIN_POLY: f64 = 1.98e-8;
+// max relative error in exact arithmetic over [1, 8), which covers every
+// input: x * 8 doubles the seeds' and so every step's result exactly
+const CBRT_STEP: f64 = 4.93e-7;
+const CBRT_ACCURATE_STEPS: f64 = 4.38e-14;
+const CBRT_THROUGHPUT_STEPS: f64 = 6.17e-6;
+// cbrt_fast's seed moves by 256 ulps every 3 binades, so over all of
+// [1e-27, 1e27], where its accuracy is measured, instead
+const CBRT_FAST_STEPS: f64 = 4.39e-5;
+const CBRT_FAST_DOMAIN: (f32, f32) = (1e-27, 1e27);
+// a bound on |tau - Df32 tau|, f64's tau is within half an ulp itself
+const DTAU: f64 = (consts::TAU.0 as f64 + consts::TAU.1 as f64 - core::f64::consts::TAU).abs()
+    + core::f64::consts::TAU * f64::EPSILON / 2.;
+
+/// An f32 with its ideal value and a bound on `|value - ideal|`.
+#[derive(Clone, Copy, Debug)]
+pub struct Tracked {
+    value: f32,
+    ideal: f64,
+    err: f64,
+}
+
+/// The integer view of a [`Tracked`], plain bits.
+#[derive(Clone, Copy, Debug)]
+pub struct TrackedBits(u32);
+
+impl Tracked {
+    pub fn exact(v: f32) -> Self {
+        Self { value: v, ideal: v as f64, err: 0. }
+    }
+    pub fn value(self) -> f32 {
+        self.value
+    }
+    pub fn ideal(self) -> f64 {
+        self.ideal
+    }
+    /// Bound on `|value - ideal|`.
+    pub fn err(self) -> f64 {
+        self.err
+    }
+
+    // `exact` is whether `value` is the exact result of the op on the f32
+    // values, then there's no rounding to add
+    fn rounded(value: f32, ideal: f64, err: f64, exact: bool) -> Self {
+        let err = match (value.is_finite(), exact) {
+            (false, _) => f64::INFINITY,
+            (true, true) => err,
+            (true, false) => err + U * value.abs() as f64 / (1. - U) + ETA,
+        };
+        Self { value, ideal, err }
+    }
+
+    // |ab - a'b'| for |a - a'| <= ea, |b - b'| <= eb
+    fn product_err(self, b: Self) -> f64 {
+        self.value.abs() as f64 * b.err + b.value.abs() as f64 * self.err + self.err * b.err
+    }
+}
+
+impl Add for Tracked {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        let (s, e) = two_sum(self.value, rhs.value);
+        Self::rounded(s, self.ideal + rhs.ideal, self.err + rhs.err, e == 0.)
+    }
+}
+
+impl Sub for Tracked {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        let (s, e) = two_sum(self.value, -rhs.value);
+        Self::rounded(s, self.ideal - rhs.ideal, self.err + rhs.err, e == 0.)
+    }
+}
+
+impl Mul for Tracked {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        let p = self.value * rhs.value;
+        // f32 products are exact in f64
+        let exact = p as f64 == self.value as f64 * rhs.value as f64;
+        Self::rounded(p, self.ideal * rhs.ideal, self.product_err(rhs), exact)
+    }
+}
+
+impl Div for Tracked {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        let (a, b) = (self.value.abs() as f64, rhs.value.abs() as f64);
+        let err = match rhs.err < b {
+            true => (a * rhs.err + b * self.err) / (b * (b - rhs.err)),
+            false => f64::INFINITY,
+        };
+        let q = self.value / rhs.value;
+        let exact = q as f64 * rhs.value as f64 == self.value as f64;
+        Self::rounded(q, self.ideal / rhs.ideal, err, exact)
+    }
+}
+
+impl Neg for Tracked {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self { value: -self.value, ideal: -self.ideal, err: self.err }
+    }
+}
+
+macro_rules! bits_op {
+    ($trait:ident, $method:ident, $eval:expr) => {
+        impl $trait for TrackedBits {
+            type Output = Self;
+            fn $method(self, rhs: Self) -> Self {
+                let eval: fn(u32, u32) -> u32 = $eval;
+                TrackedBits(eval(self.0, rhs.0))
+            }
+        }
+        impl $trait<u32> for TrackedBits {
+            type Output = Self;
+            fn $method(self, rhs: u32) -> Self {
+                $trait::$method(self, TrackedBits(rhs))
+            }
+        }
+    };
+}
+bits_op!(Add, add, u32::wrapping_add);
+bits_op!(Sub, sub, u32::wrapping_sub);
+bits_op!(Mul, mul, u32::wrapping_mul);
+bits_op!(Div, div, |a, b| a / b);
+bits_op!(BitAnd, bitand, |a, b| a & b);
+bits_op!(BitOr, bitor, |a, b| a | b);
+bits_op!(BitXor, bitxor, |a, b| a ^ b);
+bits_op!(Shl, shl, |a, b| a << b);
+bits_op!(Shr, shr, |a, b| a >> b);
+
+impl Bits for TrackedBits {
+    fn lit(v: u32) -> Self {
+        TrackedBits(v)
+    }
+    fn wrapping_add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
+}
+
+impl Float for Tracked {
+    type Bits = TrackedBits;
+
+    fn lit(v: f32) -> Self {
+        Self::exact(v)
+    }
+    fn to_bits(self) -> TrackedBits {
+        TrackedBits(self.value.to_bits())
+    }
+    fn from_bits(b: TrackedBits) -> Self {
+        Self::exact(f32::from_bits(b.0))
+    }
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        let v = self.value.mul_add(b.value, c.value);
+        let (s, e) = two_sum(self.value as f64 * b.value as f64, c.value as f64);
+        Self::rounded(
+            v,
+            self.ideal.mul_add(b.ideal, c.ideal),
+            self.product_err(b) + c.err,
+            e == 0. && s == v as f64,
+        )
+    }
+    fn floor(self) -> Self {
+        Self::exact(self.value.floor())
+    }
+    fn round(self) -> Self {
+        Self::exact(self.value.round())
+    }
+    fn abs(self) -> Self {
+        Self { value: self.value.abs(), ideal: self.ideal.abs(), err: self.err }
+    }
+    fn mulsign(self, y: Self) -> Self {
+        match y.value.is_sign_negative() {
+            true => -self,
+            false => self,
+        }
+    }
+
+    fn two_sum(self, b: Self) -> (Self, Self) {
+        let (s, e) = two_sum(self.value, b.value);
+        if !s.is_finite() {
+            return (Self::rounded(s, self.ideal + b.ideal, 0., false), Self::exact(e));
+        }
+        let head = Self {
+            value: s,
+            ideal: self.ideal + b.ideal - e as f64,
+            err: self.err + b.err,
+        };
+        (head, Self::exact(e))
+    }
+
+    fn quick_two_sum(self, b: Self) -> (Self, Self) {
+        // only error-free when |self| >= |b|, otherwise it's just rounded ops
+        if self.value.abs() >= b.value.abs() {
+            return self.two_sum(b);
+        }
+        let s = self + b;
+        (s, b - (s - self))
+    }
+
+    fn two_prod(self, b: Self) -> (Self, Self) {
+        let p = self.value * b.value;
+        // the tail is only exact if it doesn't underflow
+        if !p.is_finite() || p.abs() < 2f32.powi(-100) {
+            let p = self * b;
+            return (p, self.mul_add(b, -p));
+        }
+        let e = self.value.mul_add(b.value, -p);
+        let head = Self {
+            value: p,
+            ideal: self.ideal * b.ideal - e as f64,
+            err: self.product_err(b),
+        };
+        (head, Self::exact(e))
+    }
+
+    fn two_mul_add(self, b: Self, c: Self) -> (Self, Self) {
+        let y = self * b + c;
+        let e = self.mul_add(b, c - y);
+        if !y.value.is_finite() {
+            return (y, e);
+        }
+        // the pair is meant to add up to a * b + c, so whatever it misses by
+        // goes on the head and the tail is taken as is. f32 products are
+        // exact in f64, the two-sum makes the sum exact too
+        let (s, t) = two_sum(self.value as f64 * b.value as f64, c.value as f64);
+        let (dy, de) = (y.value as f64 - s, e.value as f64 - t);
+        let miss = (dy + de).abs() + (dy.abs() + de.abs()) * f64::EPSILON;
+        let head = Self {
+            value: y.value,
+            ideal: self.ideal * b.ideal + c.ideal - e.value as f64,
+            err: self.product_err(b) + c.err + miss,
+        };
+        (head, Self::exact(e.value))
+    }
+}
+
+fn two_sum<T: Copy + Add<Output = T> + Sub<Output = T>>(a: T, b: T) -> (T, T) {
+    let s = a + b;
+    let v = s - a;
+    (s, (a - (s - v)) + (b - v))
+}
+
+/// The worst case over a set of inputs, in ulps of the f32 result.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Certificate {
+    pub samples: usize,
+    /// Largest rounding error bound.
+    pub rounding: f64,
+    /// Largest approximation error bound, on `|ideal - f(x)|`.
+    pub approximation: f64,
+    /// Largest distance to the correctly rounded result that the two
+    /// together allow, comparable to [`crate::accuracy::ulp_error`].
+    pub max_ulp: u64,
+    /// Largest absolute error the two allow, tighter than `max_ulp` where
+    /// the result is near a zero of the function.
+    pub max_abs: f64,
+    /// The input where `max_ulp` happens.
+    pub worst: f32,
+}
+
+fn round_down(y: f64) -> f32 {
+    let f = y as f32;
+    if f as f64 > y {
+        f.next_down()
+    } else {
+        f
+    }
+}
+
+fn round_up(y: f64) -> f32 {
+    let f = y as f32;
+    if (f as f64) < y {
+        f.next_up()
+    } else {
+        f
+    }
+}
+
+/// Bound the error of a kernel against `reference` on every input, the
+/// reference only gives the size of an ulp.
+pub fn certify(kernel: &Kernel, reference: fn(f64) -> f64, inputs: impl Iterator<Item = f32>) -> Certificate {
+    let mut c = Certificate::default();
+    for x in inputs {
+        let t = (kernel.tracked)(Tracked::exact(x));
+        let expected = reference(x as f64);
+        let ulp = {
+            let r = (expected as f32).abs();
+            (r.next_up() - r) as f64
+        };
+        let approximation = (kernel.approximation)(x);
+        let b = t.err + approximation + SHADOW * expected.abs();
+        let max_ulp = match b.is_finite() && t.value.is_finite() {
+            true => {
+                let v = ordered(t.value);
+                let lo = ordered(round_down(t.value as f64 - b));
+                let hi = ordered(round_up(t.value as f64 + b));
+                v.abs_diff(lo).max(v.abs_diff(hi))
+            }
+            false => u64::MAX,
+        };
+        c.samples += 1;
+        c.max_abs = c.max_abs.max(b);
+        c.rounding = c.rounding.max(t.err / ulp);
+        c.approximation = c.approximation.max(approximation / ulp);
+        if max_ulp > c.max_ulp {
+            c.max_ulp = max_ulp;
+            c.worst = x;
+        }
+    }
+    c
+}
+
+pub struct Kernel {
+    pub name: &'static str,
+    pub f: fn(f32) -> f32,
+    pub tracked: fn(Tracked) -> Tracked,
+    /// Bound on `|ideal - f(x)|` at x.
+    pub approximation: fn(f32) -> f64,
+}
+
+pub const KERNELS: &[Kernel] = &[
+    Kernel {
+        name: "cbrt",
+        f: crate::cbrt,
+        tracked: kernels::cbrt,
+        approximation: |x| CBRT_STEP * (x as f64).abs().cbrt(),
+    },
+    Kernel {
+        name: "cbrt_accurate",
+        f: crate::cbrt_accurate,
+        tracked: kernels::cbrt_accurate,
+        approximation: |x| CBRT_ACCURATE_STEPS * (x as f64).abs().cbrt(),
+    },
+    Kernel {
+        name: "cbrt_throughput",
+        f: crate::cbrt_throughput,
+        tracked: kernels::cbrt_throughput,
+        approximation: |x| CBRT_THROUGHPUT_STEPS * (x as f64).abs().cbrt(),
+    },
+    Kernel {
+        name: "cbrt_fast",
+        f: crate::cbrt_fast,
+        tracked: kernels::cbrt_fast,
+        approximation: |x| match (CBRT_FAST_DOMAIN.0..=CBRT_FAST_DOMAIN.1).contains(&x) {
+            true => CBRT_FAST_STEPS * (x as f64).cbrt(),
+            false => f64::INFINITY,
+        },
+    },
+    Kernel {
+        name: "exp2",
+        f: crate::exp2,
+        tracked: kernels::exp2,
+        approximation: |x| EXP2_POLY * (x as f64).exp2(),
+    },
+    Kernel {
+        name: "log_2",
+        f: crate::log_2,
+        tracked: kernels::log_2,
+        approximation: |_| LOG2_RATIONAL,
+    },
+    Kernel {
+        name: "sin",
+        f: crate::sin,
+        tracked: kernels::sin,
+        approximation: sin_approximation,
+    },
+    Kernel {
+        name: "cos",
+        f: crate::cos,
+        tracked: kernels::cos,
+        approximation: cos_approximation,
+    },
+];
+
+// Both reduce to z in [-pi/2, pi/2] with sin(x) = sin(z) or cos(x) =
+// sin(-z), z = |x + q tau| - pi/2 but for Df32's tau and pi/2 = tau/4 (x
+// negated for cos). The kernels add the halves of x + q tau after taking
+// the absolute value of the head, with the tail's sign from the computed
+// head `y`. Returns their z in exact arithmetic and a bound on its distance
+// to the z of the real tau.
+fn reduced(q: f32, x: f32, y: f32) -> (f64, f64) {
+    let (tau, hpi) = (consts::TAU, consts::FRAC_PI_2);
+    let (q, x) = (q as f64, x as f64);
+    let (hi, lo) = (q * tau.0 as f64, q * tau.1 as f64);
+    let s = (hi + x).signum();
+    let lo_sign = if y.is_sign_negative() { -1. } else { 1. };
+    // in order of cancellation, x can be far below tau
+    let z = (s * hi - hpi.0 as f64) + (lo_sign * lo - hpi.1 as f64) + s * x;
+    // a tail that flips the sign of the pair costs nothing, it only mirrors
+    // z around the quarter turn and sin is even there
+    let w = hi + x + lo;
+    // the quarter turn and q tau cancel out where w keeps its sign
+    let turns = match w.abs() > q.abs() * DTAU {
+        true => (w.signum() * q - 0.25).abs(),
+        false => q.abs() + 0.25,
+    };
+    (z, turns * DTAU)
+}
+
+fn sin_approximation(x: f32) -> f64 {
+    let (tau, rtau) = (consts::TAU, consts::FRAC_1_TAU);
+    let q = 0.25 - x.mul_add(rtau.0, 0.25).round();
+    let (z, err) = reduced(q, x, q * tau.0 + x);
+    SIN_POLY * z.abs() + err
+}
+
+fn cos_approximation(x: f32) -> f64 {
+    let (tau, rtau) = (consts::TAU, consts::FRAC_1_TAU);
+    let q = (x * rtau.0).round();
+    let (z, err) = reduced(q, -x, q.mul_add(tau.0, -x));
+    SIN_POLY * z.abs() + err
+}
+
+/// `n` consecutive f32s from `start` up.
+pub fn consecutive(start: f32, n: usize) -> impl Iterator<Item = f32> {
+    std::iter::successors(Some(start), |x| Some(x.next_up())).take(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accuracy::{ulp_error, FUNCTIONS};
+
+    #[test]
+    fn tracked_values_match() {
+        for k in KERNELS {
+            for x in [0.1, 1., 3.7, 100., 12345.] {
+                let t = (k.tracked)(Tracked::exact(x));
+                assert_eq!(t.value().to_bits(), (k.f)(x).to_bits(), "{} at {x}", k.name);
+            }
+        }
+    }
+
+    #[test]
+    fn exact_ops() {
+        let (a, b) = (Tracked::exact(1.), Tracked::exact(f32::EPSILON / 4.));
+        let (s, e) = a.two_sum(b);
+        assert_eq!((s.err(), e.err()), (0., 0.));
+        assert_eq!(s.ideal() + e.ideal(), 1. + f32::EPSILON as f64 / 4.);
+        // a plain add rounds b away and says so
+        let s = a + b;
+        assert_eq!(s.value(), 1.);
+        assert!(s.err() >= f32::EPSILON as f64 / 4.);
+        assert!((s.ideal() - s.value() as f64).abs() <= s.err());
+        // ops that happen to be exact add nothing
+        let three = Tracked::exact(3.);
+        assert_eq!((a + a).err(), 0.);
+        assert_eq!((a * three - a).err(), 0.);
+        assert_eq!((three / Tracked::exact(4.)).err(), 0.);
+        assert_eq!(three.mul_add(three, -a).err(), 0.);
+        assert!((a / three).err() > 0.);
+    }
+
+    #[test]
+    fn bound_holds() {
+        // x/3 rounded three times, the bound has to cover the real distance
+        let x = Tracked::exact(1.);
+        let y = x / Tracked::exact(3.) * Tracked::exact(3.) - x;
+        assert!(y.err() >= (y.value() as f64 - y.ideal()).abs());
+        assert!(y.err() < 4. * U);
+    }
+
+    fn exact_ideal(f: fn(Tracked) -> Tracked, x: f32) -> f64 {
+        f(Tracked::exact(x)).ideal()
+    }
+
+    // every `step`th f32 from `start` to `end`
+    fn every(start: f32, end: f32, step: usize) -> impl Iterator<Item = f32> {
+        (start.to_bits()..=end.to_bits()).step_by(step).map(f32::from_bits)
+    }
+
+    // the approximation constants' error curves, each the max over every
+    // `step`th f32 of the reduced argument or seed period
+    fn approximation_curves(step: usize) -> [(&'static str, f64, f64); 7] {
+        let max = |xs: &mut dyn Iterator<Item = f32>, err: &dyn Fn(f32) -> f64| xs.map(err).fold(0., f64::max);
+        let cbrt = |f: fn(Tracked) -> Tracked, (start, end): (f32, f32)| {
+            max(&mut every(start, end, step), &|x| (exact_ideal(f, x) / (x as f64).cbrt() - 1.).abs())
+        };
+        let period = (1., 8f32.next_down());
+        [
+            (
+                "exp2",
+                EXP2_POLY,
+                max(&mut every(0., 1f32.next_down(), step), &|f| {
+                    (exact_ideal(kernels::exp2, f) / (f as f64).exp2() - 1.).abs()
+                }),
+            ),
+            (
+                "log_2",
+                LOG2_RATIONAL,
+                max(&mut every(1., 2f32.next_down(), step), &|m| {
+                    (exact_ideal(kernels::log_2, m) - (m as f64).log2()).abs()
+                }),
+            ),
+            (
+                "sin",
+                SIN_POLY,
+                max(&mut every(f32::from_bits(1), core::f32::consts::FRAC_PI_2, step), &|z| {
+                    ((exact_ideal(kernels::sinf_poly, z) - (z as f64).sin()) / z as f64).abs()
+                }),
+            ),
+            ("cbrt", CBRT_STEP, cbrt(kernels::cbrt, period)),
+            ("cbrt_accurate", CBRT_ACCURATE_STEPS, cbrt(kernels::cbrt_accurate, period)),
+            ("cbrt_throughput", CBRT_THROUGHPUT_STEPS, cbrt(kernels::cbrt_throughput, period)),
+            ("cbrt_fast", CBRT_FAST_STEPS, cbrt(kernels::cbrt_fast, CBRT_FAST_DOMAIN)),
+        ]
+    }
+
+    #[test]
+    fn approximation_bounds() {
+        for (name, bound, max) in approximation_curves(4099) {
+            assert!(max <= bound, "{name}: {max:e} > {bound:e}");
+        }
+    }
+
+    // cargo test --release -- --ignored approximation_exhaustive
+    #[test]
+    #[ignore]
+    fn approximation_exhaustive() {
+        for (name, bound, max) in approximation_curves(1) {
+            println!("{name}: {max:e}, bound {bound:e}");
+            assert!(max <= bound);
+        }
+    }
+
+    #[test]
+    fn reduction_error() {
+        // the Df32 tau's error cancels against the quarter turn near 0
+        let x = 1e-30;
+        assert_eq!(sin_approximation(x), SIN_POLY * x as f64);
+        assert!(sin_approximation(1000.) < 2. * SIN_POLY);
+    }
+
+    // every input in a stretch at the start of each domain, the certified
+    // bound has to be at least the measured error
+    #[test]
+    fn certificates_cover_measured() {
+        for k in KERNELS {
+            let func = FUNCTIONS.iter().find(|f| f.name == k.name).unwrap();
+            for seg in func.segments {
+                let cert = certify(k, func.reference, consecutive(seg.start, 1 << 14));
+                let measured = consecutive(seg.start, 1 << 14)
+                    .map(|x| ulp_error(k.f, func.reference, x))
+                    .max()
+                    .unwrap();
+                assert!(
+                    measured <= cert.max_ulp,
+                    "{} {}: measured {measured} ulp, certified {cert:?}",
+                    k.name,
+                    seg.name
+                );
+            }
+        }
+    }
+
+    // cargo test --release -- --ignored exhaustive_binade
+    #[test]
+    #[ignore]
+    fn exhaustive_binade() {
+        for k in KERNELS {
+            let func = FUNCTIONS.iter().find(|f| f.name == k.name).unwrap();
+            for seg in func.segments {
+                let cert = certify(k, func.reference, consecutive(seg.start, 1 << 23));
+                let measured = consecutive(seg.start, 1 << 23)
+                    .map(|x| ulp_error(k.f, func.reference, x))
+                    .max()
+                    .unwrap();
+                println!("{} {}: measured {measured}, certified {}", k.name, seg.name, cert.max_ulp);
+                assert!(measured <= cert.max_ulp);
+            }
+        }
+    }
+}
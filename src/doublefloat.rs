@@ -1,4 +1,7 @@
-use std::ops::{Add, Div, Mul, Neg, Sub};
+use core::iter::{Product, Sum};
+use core::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::scalar::Float;
 
 /// Double-float: an unevaluated sum (.0, .1) where |.1| <= ulp(.0)/2.
 /// Provides ~2× f32 precision (~14 decimal digits) using pairs of f32s.
@@ -8,12 +11,10 @@ pub struct Df32(pub f32, pub f32);
 // ── Helpers ──────────────────────────────────────────────────────────
 
 const SIGN_MASK: u32 = 0x80000000;
-const EXPONENT_MASK: u32 = 0x7f800000;
-const MANTISSA_MASK: u32 = 0x007fffff;
 
 #[inline(always)]
 fn fma(a: f32, b: f32, c: f32) -> f32 {
-    a.mul_add(b, c)
+    Float::mul_add(a, b, c)
 }
 #[inline(always)]
 fn mulsign(x: f32, y: f32) -> f32 {
@@ -22,7 +23,7 @@ fn mulsign(x: f32, y: f32) -> f32 {
 
 /// Error-free addition (Knuth two-sum).
 #[inline(always)]
-fn two_sum(a: f32, b: f32) -> (f32, f32) {
+pub(crate) fn two_sum(a: f32, b: f32) -> (f32, f32) {
     let s = a + b;
     let v = s - a;
     let e = (a - (s - v)) + (b - v);
@@ -46,6 +47,19 @@ impl Df32 {
         Self(v, 0.0)
     }
 
+    /// Split an f64 into a round-to-nearest head and the nearest f32 to
+    /// the remainder. Usable in consts, so constants can be written as
+    /// `Df32::from_f64(1. / TAU)` instead of splitting by hand.
+    #[inline(always)]
+    pub const fn from_f64(x: f64) -> Self {
+        let hi = x as f32;
+        if hi.is_infinite() {
+            return Self(hi, 0.0);
+        }
+        // x - hi is exact, the heads share an exponent
+        Self(hi, (x - hi as f64) as f32)
+    }
+
     /// Build from the product of two f32s (error-free).
     #[inline(always)]
     pub fn from_mul(a: f32, b: f32) -> Self {
@@ -271,10 +285,55 @@ impl Div<Df32> for f32 {
     }
 }
 
+// ── Iterators ────────────────────────────────────────────────────────
+
+impl Sum for Df32 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Df32::from_f32(0.0), |a, b| a + b)
+    }
+}
+
+/// Compensated sum of plain f32s, see also `sum_compensated` for slices.
+impl Sum<f32> for Df32 {
+    fn sum<I: Iterator<Item = f32>>(iter: I) -> Self {
+        iter.fold(Df32::from_f32(0.0), |a, b| a + b)
+    }
+}
+
+impl Product for Df32 {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Df32::from_f32(1.0), |a, b| a * b)
+    }
+}
+
+impl Product<f32> for Df32 {
+    fn product<I: Iterator<Item = f32>>(iter: I) -> Self {
+        iter.fold(Df32::from_f32(1.0), |a, b| a * b)
+    }
+}
+
+// ── Constants ────────────────────────────────────────────────────────
+
+/// Pre-split double-float constants, named like `core::f64::consts`.
+pub mod consts {
+    use super::Df32;
+
+    pub const PI: Df32 = Df32::from_f64(core::f64::consts::PI);
+    pub const TAU: Df32 = Df32::from_f64(core::f64::consts::TAU);
+    pub const FRAC_PI_2: Df32 = Df32::from_f64(core::f64::consts::FRAC_PI_2);
+    pub const FRAC_1_PI: Df32 = Df32::from_f64(core::f64::consts::FRAC_1_PI);
+    /// 1/τ
+    pub const FRAC_1_TAU: Df32 = Df32::from_f64(0.5 * core::f64::consts::FRAC_1_PI);
+    pub const LN_2: Df32 = Df32::from_f64(core::f64::consts::LN_2);
+    pub const LOG2_E: Df32 = Df32::from_f64(core::f64::consts::LOG2_E);
+    pub const LOG2_10: Df32 = Df32::from_f64(core::f64::consts::LOG2_10);
+    pub const SQRT_2: Df32 = Df32::from_f64(core::f64::consts::SQRT_2);
+}
+
 // ── Display ──────────────────────────────────────────────────────────
 
-impl std::fmt::Display for Df32 {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for Df32 {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let val = self.0 as f64 + self.1 as f64;
         write!(f, "{val}")
     }
@@ -287,6 +346,7 @@ mod tests {
     use super::*;
 
     #[test]
+    #[allow(clippy::approx_constant)]
     fn test_tuple_access() {
         let x = Df32(3.14, 1e-10);
         assert_eq!(x.0, 3.14);
@@ -313,6 +373,46 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_f64_split() {
+        for x in [1.0 / 3.0, -0.1, 1e-30, 12345.678901234, core::f64::consts::E] {
+            let d = Df32::from_f64(x);
+            assert_eq!(d.0, x as f32);
+            assert_eq!(d.1, (x - d.0 as f64) as f32);
+            assert!(d.1.abs() <= d.0.abs() * f32::EPSILON / 2.);
+        }
+        assert_eq!(Df32::from_f64(f64::MAX).0, f32::INFINITY);
+        assert_eq!(Df32::from_f64(f64::MAX).1, 0.0);
+        const THIRD: Df32 = Df32::from_f64(1.0 / 3.0);
+        assert_eq!(THIRD.0, 1.0 / 3.0_f32);
+    }
+
+    #[test]
+    fn test_consts() {
+        use core::f64::consts as f64c;
+        // (constant, f64 head, f64 tail) with the tails from 300 bit mpmath
+        let cases = [
+            (consts::PI, f64c::PI, 1.2246467991473532e-16),
+            (consts::TAU, f64c::TAU, 2.4492935982947064e-16),
+            (consts::FRAC_PI_2, f64c::FRAC_PI_2, 6.123233995736766e-17),
+            (consts::FRAC_1_PI, f64c::FRAC_1_PI, -1.9678676675182486e-17),
+            (consts::FRAC_1_TAU, 0.5 * f64c::FRAC_1_PI, -9.839338337591243e-18),
+            (consts::LN_2, f64c::LN_2, 2.3190468138462996e-17),
+            (consts::LOG2_E, f64c::LOG2_E, 2.0355273740931033e-17),
+            (consts::LOG2_10, f64c::LOG2_10, 1.661617516973592e-16),
+            (consts::SQRT_2, f64c::SQRT_2, -9.667293313452913e-17),
+        ];
+        for (c, hi, lo) in cases {
+            assert_eq!(c.0, hi as f32);
+            // remaining error after both f32 parts, within half an ulp of the tail
+            let err = ((hi - c.0 as f64) - c.1 as f64) + lo;
+            assert!(
+                err.abs() <= c.1.abs() as f64 * f32::EPSILON as f64 / 2.,
+                "{c} off by {err:e}"
+            );
+        }
+    }
+
     #[test]
     fn test_cross_type_ops() {
         let d = Df32::from_f32(3.0);
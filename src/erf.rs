@@ -0,0 +1,226 @@
+//! erf, erfc and erfinv. erfc is in two pieces: 1 - erf(z) from an odd
+//! polynomial below 0.84375, and e^(-z²) erfcx(z) above, erfcx = e^(z²)
+//! erfc(z) being smooth enough for a few centered polynomials, so there's
+//! no division like a rational fit would need. e^(-z²) goes through `exp2`
+//! with z² exact, so the tail doesn't lose the bits of z² a plain square
+//! would round away, and erfc stays accurate down to where it underflows at
+//! 10.05. erfinv is Giles' single precision approximation refined by a
+//! Newton step on erf or erfc.
+
+use crate::doublefloat::consts::LN_2;
+use crate::exp::exp_mul;
+use crate::poly::horner;
+use crate::scalar::{fma, hex, sqrt};
+use crate::{log1p, log_2, Df32};
+
+// erf(z)/z - 1 as a polynomial in z², |z| < 0.84375
+#[inline(always)]
+fn erf_small(z2: f32) -> f32 {
+    // erf on [0, 0.84375], x + x y(x²), relative error 6.2e-9
+    horner(
+        z2,
+        [
+            hex(0x3e0375d4),
+            hex(0xbec09395),
+            hex(0x3de71303),
+            hex(0xbcdba058),
+            hex(0x3ba5e1aa),
+            hex(0xba272b69),
+        ],
+    )
+}
+
+// erfcx on [0.84375, 10.1], each piece in powers of (a - center), relative
+// error below 2.7e-8
+const ERFCX_START: [f32; 5] = [0.84375, 1.5, 2.5, 4., 6.];
+const ERFCX_CENTER: [f32; 5] = [1.17, 2., 3.25, 5., 8.];
+#[rustfmt::skip]
+const ERFCX: [[u32; 9]; 5] = [
+    [0x3ec53e05, 0xbe685dd0, 0x3df53b47, 0xbd6d154b, 0x3cd51379, 0xbc33d2d0, 0x3b8fc83d, 0xbae1c710, 0x3a2263bc],
+    [0x3e82c339, 0xbddab81a, 0x3d2b395d, 0xbc7d4f22, 0x3bb24854, 0xbaefd8b1, 0x3a1b37ce, 0xb94ba3f9, 0x3873be44],
+    [0x3e2a53d0, 0xbd415428, 0x3c53f6ee, 0xbb6126ad, 0x3a684359, 0xb968c650, 0x3863f2c5, 0xb7699016, 0x3658da84],
+    [0x3de2b91d, 0xbcaec21a, 0x3b8467c1, 0xba455ed3, 0x3910d002, 0xb7d0fb6e, 0x3694e9e4, 0xb560227e, 0x341acb74],
+    [0x3d8f5462, 0xbc0d2c3d, 0x3a8a0855, 0xb9060278, 0x37812755, 0xb5f5cac2, 0x346b28c4, 0xb2fcb820, 0x316097fa],
+];
+
+#[inline(always)]
+fn erfcx(a: f32) -> f32 {
+    // erfc is 0 past 10.06 anyway, the last piece just has to stay finite
+    let a = a.min(10.1);
+    // picked with selects rather than an index, which vectorizes as blends
+    // instead of gathers
+    let (mut center, mut c) = (ERFCX_CENTER[0], ERFCX[0]);
+    for i in 1..5 {
+        if a >= ERFCX_START[i] {
+            (center, c) = (ERFCX_CENTER[i], ERFCX[i]);
+        }
+    }
+    horner(a - center, c.map(hex))
+}
+
+/// m erfc(z) with z² given as h + l, exact or at least more precise than
+/// z * z. m goes into the tail before the scaling, so a product that's
+/// normal is still accurate where erfc alone is subnormal. Both pieces are
+/// evaluated and selected, so a loop over it vectorizes.
+#[inline(always)]
+pub(crate) fn erfc_sq(z: f32, h: f32, l: f32, m: f32) -> f32 {
+    let a = z.abs();
+    let small = m * fma(-z, erf_small(h), 1. - z);
+    let neg = z < 0.;
+    let r = exp_mul(-h, -l, if neg { 1. } else { m } * erfcx(a));
+    let tail = if neg { m * (2. - r) } else { r };
+    // NaN takes the tail, where it stays NaN
+    if a < 0.84375 {
+        small
+    } else {
+        tail
+    }
+}
+
+#[inline(always)]
+pub fn erfc(x: f32) -> f32 {
+    // 0 or 2 past ±20, and the square stays finite
+    let c = x.clamp(-20., 20.);
+    let Df32(h, l) = Df32::from_mul(c, c);
+    erfc_sq(c, h, l, 1.)
+}
+
+#[inline(always)]
+pub fn erf(x: f32) -> f32 {
+    let a = x.abs();
+    let small = fma(x, erf_small(x * x), x);
+    let c = a.clamp(0., 20.);
+    let Df32(h, l) = Df32::from_mul(c, c);
+    // erfc is at most 0.24 here, nothing cancels
+    let large = (1. - erfc_sq(c, h, l, 1.)).copysign(x);
+    if a < 0.84375 {
+        small
+    } else {
+        large
+    }
+}
+
+/// The inverse of `erf` on [-1, 1], ±inf at ±1 and NaN outside.
+#[inline(always)]
+#[allow(clippy::excessive_precision)]
+pub fn erfinv(x: f32) -> f32 {
+    let a = x.abs();
+    // w = -ln(1 - x²), log1p while x² is small, then 1 - x² from one fma
+    // so the distance to 1 isn't rounded away
+    let w_small = -log1p(-a * a);
+    let w_large = -log_2(fma(-a, a, 1.)) * LN_2.0;
+    let w = if a < 0.7 { w_small } else { w_large };
+    // Giles, "Approximating the erfinv function", relative error 3.7e-7
+    let central = horner(
+        w - 2.5,
+        [
+            1.50140941,
+            0.246640727,
+            -0.00417768164,
+            -0.00125372503,
+            0.00021858087,
+            -4.39150654e-6,
+            -3.5233877e-6,
+            3.43273939e-7,
+            2.81022636e-8,
+        ],
+    );
+    let tail = horner(
+        sqrt(w) - 3.,
+        [
+            2.83297682,
+            1.00167406,
+            0.00943887047,
+            -0.0076224613,
+            0.00573950773,
+            -0.00367342844,
+            0.00134934322,
+            0.000100950558,
+            -0.000200214257,
+        ],
+    );
+    let y = a * if w < 5. { central } else { tail };
+    // one Newton step, on erf while it has the bits and on erfc, whose
+    // target 1 - a is exact from 0.5 up, nearer 1
+    let Df32(h, l) = Df32::from_mul(y, y);
+    let residual = if a < 0.5 {
+        fma(y, erf_small(h), y) - a
+    } else {
+        (1. - a) - erfc_sq(y, h, l, 1.)
+    };
+    let y = y - residual / exp_mul(-h, -l, core::f32::consts::FRAC_2_SQRT_PI);
+    let y = if a == 1. { f32::INFINITY } else { y };
+    // NaN stays NaN, past 1 the log above isn't
+    let y = if a > 1. { f32::NAN } else { y };
+    y.copysign(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the reference lives in the std-only accuracy module
+    #[test]
+    #[cfg(feature = "std")]
+    fn erfc_pieces() {
+        use crate::accuracy::erfc_reference;
+        let mut worst = 0f64;
+        let mut x = -4f32;
+        while x < 10.1 {
+            let Df32(h, l) = Df32::from_mul(x, x);
+            let y = erfc_sq(x, h, l, 1.) as f64;
+            let expected = erfc_reference(x as f64);
+            // relative error means nothing once the result is subnormal
+            if expected > f32::MIN_POSITIVE as f64 {
+                worst = worst.max(((y - expected) / expected).abs());
+            }
+            x += 1. / 1024.;
+        }
+        assert!(worst < 4e-7, "{worst}");
+        let inf = f32::INFINITY;
+        assert_eq!(erfc_sq(inf, inf, 0., 1.), 0.);
+        assert_eq!(erfc_sq(-inf, inf, 0., 1.), 2.);
+        assert_eq!(erfc_sq(20., 400., 0., 1.), 0.);
+        assert!(erfc_sq(f32::NAN, f32::NAN, 0., 1.).is_nan());
+        assert_eq!(erfc_sq(0., 0., 0., 1.), 1.);
+    }
+
+    #[test]
+    fn special_values() {
+        let inf = f32::INFINITY;
+        assert_eq!(erf(inf), 1.);
+        assert_eq!(erf(-inf), -1.);
+        assert_eq!(erf(4.), 1.);
+        assert_eq!(erfc(inf), 0.);
+        assert_eq!(erfc(-inf), 2.);
+        assert_eq!(erfc(1e30), 0.);
+        assert_eq!(erfc(0.), 1.);
+        assert!(erfc(10.) > 0.);
+        assert_eq!(erfinv(1.), inf);
+        assert_eq!(erfinv(-1.), -inf);
+        assert!(erfinv(1.5).is_nan());
+        assert!(erfinv(-inf).is_nan());
+        for f in [erf, erfc, erfinv] {
+            assert!(f(f32::NAN).is_nan());
+        }
+        for f in [erf, erfinv] {
+            assert_eq!(f(-0.).to_bits(), (-0f32).to_bits());
+            assert_eq!(f(0.).to_bits(), 0);
+            assert_eq!(f(1e-40), f(-1e-40).abs());
+        }
+    }
+
+    #[test]
+    fn erfinv_inverts_erf() {
+        let mut x = -3.5f32;
+        while x < 3.5 {
+            let y = erfinv(erf(x));
+            // erf flattens out, the inverse can only be as good as erf's
+            // last bit allows
+            let slope = core::f32::consts::FRAC_2_SQRT_PI * (-x * x).exp();
+            let tolerance = 4e-7 * x.abs() + f32::EPSILON * 2. / slope;
+            assert!((y - x).abs() <= tolerance, "{x}: {y}");
+            x += 1. / 256.;
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! The exponentials the functions built on `exp2` share. The kernel only
+//! covers [-126, 128), these scale out to inf and down through the
+//! subnormals, and multiply in a factor before the scaling so a result that
+//! ends up subnormal is still only rounded once. e^x takes its argument as
+//! a head and tail, with the log2(e) product split exactly.
+
+use crate::doublefloat::consts::{LN_2, LOG2_E};
+use crate::scalar::fma;
+use crate::Df32;
+
+/// m 2^a for any a. Selects instead of branches, so it vectorizes.
+#[inline(always)]
+pub(crate) fn exp2_mul(a: f32, m: f32) -> f32 {
+    let a = a.clamp(-250., 250.);
+    // two steps of 2^±64, the kernel stays in range and the first step
+    // can't overflow or underflow on its own
+    let (a, s) = if a > 100. {
+        (a - 128., f32::from_bits(0x5f800000))
+    } else if a < -100. {
+        (a + 128., f32::from_bits(0x1f800000))
+    } else {
+        (a, 1.)
+    };
+    crate::exp2(a) * m * s * s
+}
+
+/// m e^(h + l), l a small tail of the argument or 0.
+#[inline(always)]
+pub(crate) fn exp_mul(h: f32, l: f32, m: f32) -> f32 {
+    // past the range either way, and keeps inf out of the tail below
+    let h = h.clamp(-200., 200.);
+    // a tail is under half an ulp of h, bigger only when h was clamped
+    let l = l.clamp(-0.5, 0.5);
+    let Df32(t, te) = Df32::from_mul(h, LOG2_E.0);
+    let te = fma(h, LOG2_E.1, fma(l, LOG2_E.0, te));
+    // 2^(t + te) = 2^t (1 + te ln2) to first order
+    exp2_mul(t, fma(m * te, LN_2.0, m))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_range() {
+        assert_eq!(exp2_mul(-149., 1.), f32::from_bits(1));
+        assert_eq!(exp2_mul(-140., 3.), f32::from_bits(3 << 9));
+        assert_eq!(exp2_mul(-151., 1.), 0.);
+        assert_eq!(exp2_mul(127., 1.5), 1.5 * 2f32.powi(127));
+        assert_eq!(exp2_mul(128., 1.), f32::INFINITY);
+        assert_eq!(exp2_mul(f32::NEG_INFINITY, 1.), 0.);
+        assert!(exp2_mul(f32::NAN, 1.).is_nan());
+        assert_eq!(exp_mul(f32::INFINITY, 0., 1.), f32::INFINITY);
+        assert_eq!(exp_mul(f32::NEG_INFINITY, 0., 1.), 0.);
+        assert!(exp_mul(f32::NAN, 0., 1.).is_nan());
+        for x in [-103.5f32, -87.3, -20., -0.3, 0., 1e-3, 1., 10., 50., 88.7] {
+            let expected = (x as f64).exp();
+            let y = exp_mul(x, 0., 1.) as f64;
+            let ulp = f32::from_bits((expected as f32).to_bits() + 1) as f64 - expected as f32 as f64;
+            assert!((y - expected).abs() < 3. * ulp, "{x}: {y} vs {expected}");
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! expm1/exp2m1 and log1p/log2p1, accurate relative to the result near zero
+//! where `exp2(x) - 1` and `log_2(1 + x)` cancel. One minimax polynomial
+//! around zero each (from `remez`), the rest of the range is reduced onto it
+//! exactly, with the reduced argument carried in `Df32` so the bits the
+//! reduction cancels aren't lost. At the edges of the range the results meet
+//! `exp2`/`log_2`: -1 and inf for the exponentials, the exponent plus a log
+//! near 1 for the logarithms.
+
+use crate::doublefloat::consts::{LN_2, LOG2_E};
+use crate::poly::horner;
+use crate::scalar::{fma, hex, Float};
+use crate::Df32;
+
+// e^(r + lo) - 1 for |r| <= 0.36, lo a small tail of the argument
+#[inline(always)]
+fn expm1_poly(r: f32, lo: f32) -> f32 {
+    // expm1 on [-0.36, 0.36], relative error 4.8e-9
+    let q = horner(
+        r,
+        [0.5, hex(0x3e2aaaab), hex(0x3d2aaa26), hex(0x3c08884d), hex(0x3ab6e386), hex(0x3950cd10)],
+    );
+    let p = fma(r * r, q, r);
+    // the tail to first order, e^(r + lo) = e^r (1 + lo)
+    fma(lo, p + 1., p)
+}
+
+// 2^k (1 + m) - 1 as 2 (2^(k-1) m + 2^(k-1) - 1/2), which is exact before
+// the last rounding for k in [-24, 24] and keeps 2^(k-1) finite up to k = 128
+#[inline(always)]
+fn scale_m1(k: f32, m: f32) -> f32 {
+    let h = f32::from_bits(((k as i32 + 126) as u32) << 23);
+    2. * fma(h, m, h - 0.5)
+}
+
+#[inline(always)]
+pub fn expm1(x: f32) -> f32 {
+    if x > 88.8 {
+        return f32::INFINITY;
+    }
+    if x < -17.4 {
+        return -1.;
+    }
+    // x = k ln2 + r + lo, the product is split exactly so r keeps every
+    // bit of x the subtraction cancels
+    let k = Float::round(x * LOG2_E.0);
+    let Df32(kl, kl_err) = Df32::from_mul(k, LN_2.0);
+    let Df32(r, e) = Df32::from_add(x, -kl);
+    let lo = fma(-k, LN_2.1, e - kl_err);
+    // both signs are the sign of x, also for -0
+    scale_m1(k, expm1_poly(r, lo)).copysign(x)
+}
+
+#[inline(always)]
+pub fn exp2m1(x: f32) -> f32 {
+    if x > 128.5 {
+        return f32::INFINITY;
+    }
+    if x < -26. {
+        return -1.;
+    }
+    let k = Float::round(x);
+    // exact, then f ln2 as a Df32
+    let f = x - k;
+    let Df32(r, lo) = Df32::from_mul(f, LN_2.0);
+    let lo = fma(f, LN_2.1, lo);
+    scale_m1(k, expm1_poly(r, lo)).copysign(x)
+}
+
+/// u = 2^e m with m in [sqrt(1/2), sqrt(2)), for a positive normal u.
+/// Anything else gives garbage rather than a panic, for callers that select
+/// the result away.
+#[inline(always)]
+pub(crate) fn split_sqrt2(u: f32) -> (i32, f32) {
+    // shift the binade boundary to sqrt(1/2)
+    let bits = u.to_bits().wrapping_add(0x3f800000 - 0x3f3504f3);
+    let e = (bits >> 23) as i32 - 127;
+    (e, f32::from_bits((bits & 0x007fffff) + 0x3f3504f3))
+}
+
+// 1 + x = 2^e (1 + t + tl) with 1 + t in [sqrt(1/2), sqrt(2)), returns e and
+// ln(1 + t + tl)
+#[inline(always)]
+fn log1p_reduce(x: f32) -> (f32, f32) {
+    let Df32(u, ul) = Df32::from_add(1., x);
+    let (e, m) = split_sqrt2(u);
+    // both exact, the scaling only loses tl where it doesn't matter anymore
+    let t = m - 1.;
+    let tl = ul * f32::from_bits(((127 - e).max(1) as u32) << 23);
+    // log1p on [sqrt(1/2) - 1, sqrt(2) - 1], relative error 5.4e-9
+    let q = horner(
+        t,
+        [
+            hex(0xbefffffc),
+            hex(0x3eaaaaa8),
+            hex(0xbe800213),
+            hex(0x3e4cd1f4),
+            hex(0xbe2a13a1),
+            hex(0x3e11392a),
+            hex(0xbe079ce2),
+            hex(0x3e04223f),
+            hex(0xbd9c0cce),
+        ],
+    );
+    // ln(1 + t + tl) = ln(1 + t) + tl/(1 + t), to first order in tl
+    let c = fma(-tl, t, tl);
+    (e as f32, t + fma(t * t, q, c))
+}
+
+#[inline(always)]
+pub fn log1p(x: f32) -> f32 {
+    if x <= -1. {
+        return if x == -1. { f32::NEG_INFINITY } else { f32::NAN };
+    }
+    if x.is_nan() || x == f32::INFINITY {
+        return x;
+    }
+    let (e, p) = log1p_reduce(x);
+    fma(e, LN_2.0, fma(e, LN_2.1, p)).copysign(x)
+}
+
+#[inline(always)]
+pub fn log2p1(x: f32) -> f32 {
+    if x <= -1. {
+        return if x == -1. { f32::NEG_INFINITY } else { f32::NAN };
+    }
+    if x.is_nan() || x == f32::INFINITY {
+        return x;
+    }
+    let (e, p) = log1p_reduce(x);
+    (e + fma(p, LOG2_E.0, p * LOG2_E.1)).copysign(x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_values() {
+        let inf = f32::INFINITY;
+        for f in [expm1, exp2m1] {
+            assert_eq!(f(inf), inf);
+            assert_eq!(f(-inf), -1.);
+            assert_eq!(f(1000.), inf);
+            assert_eq!(f(-1000.), -1.);
+        }
+        assert_eq!(expm1(88.72), 88.72f32.exp_m1());
+        assert_eq!(expm1(88.73), inf);
+        assert_eq!(exp2m1(128.), inf);
+        assert_eq!(exp2m1(127.99999), 127.99999f32.exp2() - 1.);
+        for f in [log1p, log2p1] {
+            assert_eq!(f(-1.), -inf);
+            assert!(f(-1.5).is_nan());
+            assert_eq!(f(inf), inf);
+        }
+        assert_eq!(log2p1(f32::MAX), 128.);
+        for f in [expm1, exp2m1, log1p, log2p1] {
+            assert!(f(f32::NAN).is_nan());
+            assert_eq!(f(-0.).to_bits(), (-0f32).to_bits());
+            assert_eq!(f(0.).to_bits(), 0);
+        }
+    }
+
+    // the whole point, relative accuracy stays as x goes to 0
+    #[test]
+    fn tiny_arguments() {
+        type Case = (fn(f32) -> f32, fn(f64) -> f64);
+        let cases: [Case; 4] = [
+            (expm1, f64::exp_m1),
+            (exp2m1, |x| (x * core::f64::consts::LN_2).exp_m1()),
+            (log1p, f64::ln_1p),
+            (log2p1, |x| x.ln_1p() / core::f64::consts::LN_2),
+        ];
+        for (f, reference) in cases {
+            for x in [1e-30, -1e-30, 1e-10, -3e-8, 1e-5, -1e-5, 0.01, -0.2] {
+                let y = f(x);
+                let expected = reference(x as f64);
+                assert!(((y as f64 - expected) / expected).abs() < 2e-7, "{x}: {y} vs {expected}");
+            }
+        }
+    }
+}
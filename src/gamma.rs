@@ -0,0 +1,278 @@
+//! gamma, lgamma and digamma. lgamma is 0 at 1 and 2 and digamma at its
+//! root x0 = 1.4616..., so around those they come from fits that keep the
+//! zero as a factor and stay accurate relative to the result: 1/Γ(x) =
+//! 1 + (x - 1)(x - 2) h(x) on [0.5, 3), h being entire, and digamma(x) =
+//! (x - x0) r(x) on [1, 3). From 3 up both are the asymptotic series with
+//! the tail fitted by `remez` and ln x carried as a `Df32`, and gamma is
+//! e^lgamma with that head and tail as the exponent. Below the fits the
+//! recurrence shifts the argument up by one, and negative x go through the
+//! reflection formulas with `sinpi` and `cospi`. That is only accurate
+//! relative to the terms it adds up, so near the zeros lgamma and digamma
+//! have between the negative integers the error is absolute. Selects
+//! rather than branches throughout, so all three vectorize.
+
+use crate::doublefloat::consts::{LN_2, PI};
+use crate::exp::exp_mul;
+use crate::expm1::split_sqrt2;
+use crate::poly::horner;
+use crate::scalar::{fma, hex, Float};
+use crate::{cospi, log1p, sinpi, Df32};
+
+const TWO_24: f32 = 16777216.;
+const LN_PI: Df32 = Df32::from_f64(1.1447298858494002);
+// ln(2π)/2 - 1/2
+const STIRLING_C: Df32 = Df32::from_f64(0.4189385332046727);
+// lgamma overflows past this
+const LGAMMA_MAX: u32 = 0x7c44af8d;
+/// The Euler–Mascheroni constant, -digamma(1).
+pub const EULER_GAMMA: f64 = 0.5772156649015329;
+/// The positive root of digamma, 1.4616..., as an f64 head and tail.
+pub const DIGAMMA_ROOT: (f64, f64) = (1.4616321449683622, 9.549995429965697e-17);
+const ROOT: Df32 = Df32::from_f64(DIGAMMA_ROOT.0);
+
+// 1/Γ(x) - 1 for x in [0.5, 3)
+#[inline(always)]
+fn rgamma_m1(x: f32) -> f32 {
+    // h on [0.5, 3] in powers of x - 1.75, relative error 2.5e-8
+    let h = horner(
+        x - 1.75,
+        [
+            hex(0xbef07a13),
+            hex(0x3e3c0258),
+            hex(0x3cc48954),
+            hex(0xbd0c4d87),
+            hex(0x3c047bc9),
+            hex(0x3a3ae92d),
+            hex(0xba55bd93),
+            hex(0x39366e1b),
+            hex(0x359e8c34),
+            hex(0xb716dca8),
+            hex(0x35ecb0ae),
+        ],
+    );
+    // both factors are exact around their zero
+    (x - 1.) * ((x - 2.) * h)
+}
+
+// ln x for positive finite x, subnormals included. gamma exponentiates
+// x ln x, so this is 2 atanh((m - 1)/(m + 1)) with the quotient as a Df32
+// rather than the log1p polynomial, whose rounding x would multiply.
+#[inline(always)]
+pub(crate) fn ln_df(x: f32) -> Df32 {
+    let tiny = x < f32::MIN_POSITIVE;
+    let (e, m) = split_sqrt2(if tiny { x * TWO_24 } else { x });
+    let e = if tiny { e - 24 } else { e } as f32;
+    // |u| <= 0.172, the series past u is below 0.0034
+    let Df32(u, ul) = Df32(m - 1., 0.) / Df32::from_add(m, 1.);
+    let u2 = u * u;
+    let s = horner(u2, [1. / 3., 1. / 5., 1. / 7., 1. / 9.]);
+    let tail = fma(u * u2, s, ul);
+    Df32::from_mul(e, LN_2.0) + fma(e, LN_2.1, 2. * tail) + 2. * u
+}
+
+// lgamma(x) for x >= 3, (x - 1/2)(ln x - 1) + ln(2π)/2 - 1/2 + s(1/x²)/x
+#[inline(always)]
+fn lgamma_stirling(x: f32) -> Df32 {
+    // s on [0, 1/9], relative error 4.4e-8
+    let s = horner(
+        1. / (x * x),
+        [hex(0x3daaaaab), hex(0xbb360850), hex(0x3a4dc3aa), hex(0xb9eee876)],
+    ) / x;
+    let l = Df32::from_add(x, -0.5) * (ln_df(x) - 1.) + (STIRLING_C + s);
+    if x > hex(LGAMMA_MAX) {
+        Df32(f32::INFINITY, 0.)
+    } else {
+        l
+    }
+}
+
+/// ln|Γ(x)|, inf at 0 and the negative integers. Within 4 ulp for x > 0;
+/// for x < 0 within 8 ulp of the larger of lgamma(-x) and
+/// ln|π/(x sin(πx))|, which it's the difference of, so the error near its
+/// zeros between the poles is absolute.
+#[inline(always)]
+pub fn lgamma(x: f32) -> f32 {
+    // lgamma(x) = ln(π/|x sin(πx)|) - lgamma(-x), -x is exact where 1 - x
+    // isn't
+    let neg = x < -0.5;
+    let y = if neg { -x } else { x };
+    // lgamma(y) = lgamma(y + 1) - ln|y|
+    let small = y < 0.5;
+    let z = if small { y + 1. } else { y };
+    let mid = -log1p(rgamma_m1(z));
+    let Df32(h, l) = lgamma_stirling(z);
+    let (h, l) = if z < 3. { (mid, 0.) } else { (h, l) };
+    let (h, l) = if neg { (-h, -l) } else { (h, l) };
+    let a = if neg {
+        (x * sinpi(x)).abs()
+    } else if small {
+        y.abs()
+    } else {
+        1.
+    };
+    let c = if neg { LN_PI } else { Df32(0., 0.) };
+    // the terms are about the same size near the zeros for x < 0
+    let Df32(v, vl) = (c - ln_df(a)) + h;
+    let v = v + (vl + l);
+    // an overflow would be NaN in the Df32 sums
+    if (x <= 0. && Float::round(x) == x) || x > hex(LGAMMA_MAX) {
+        f32::INFINITY
+    } else {
+        v
+    }
+}
+
+/// Γ(x), ±inf at ±0 and NaN at the negative integers. Within 4 ulp for
+/// x > 0 and 6 ulp for x < 0, down to where it underflows.
+#[inline(always)]
+pub fn gamma(x: f32) -> f32 {
+    // Γ(x) = -π/(x sin(πx) Γ(-x))
+    let neg = x < -0.5;
+    let y = if neg { -x } else { x };
+    // Γ(y) = Γ(y + 1)/y
+    let small = y < 0.5;
+    let z = if small { y + 1. } else { y };
+    // 1/Γ(z) below 3 and ln Γ(z) above
+    let r = 1. + rgamma_m1(z);
+    let Df32(h, l) = lgamma_stirling(z);
+    let m = if neg {
+        -PI.0 / (x * sinpi(x))
+    } else if small {
+        1. / y
+    } else {
+        1.
+    };
+    let mid = if neg { m * r } else { m / r };
+    let big = if neg { exp_mul(-h, -l, m) } else { exp_mul(h, l, m) };
+    let v = if z < 3. { mid } else { big };
+    // poles of both signs, sin(πx) is 0 there
+    if x < 0. && Float::round(x) == x {
+        f32::NAN
+    } else {
+        v
+    }
+}
+
+/// Γ'(x)/Γ(x), ∓inf at ±0 and NaN at the negative integers. Within 4 ulp
+/// for x > 0; for x < 0 within 6 ulp of the largest of digamma(-x), 1/x
+/// and π cot(πx), which it adds up, so the error near its zeros is
+/// absolute.
+#[inline(always)]
+pub fn digamma(x: f32) -> f32 {
+    // digamma(x) = digamma(-x) - 1/x - π cot(πx)
+    let neg = x < 0.;
+    let y = if neg { -x } else { x };
+    // digamma(y) = digamma(y + 1) - 1/y
+    let small = y < 1.;
+    let z = if small { y + 1. } else { y };
+    // z - x0 from y where it's shifted, y + 1 is rounded
+    let (a, b) = if small { (y, 1. - ROOT.0) } else { (z, -ROOT.0) };
+    let t = z - 2.;
+    // r on [1, 3] in powers of x - 2, relative error 2e-8
+    let p = horner(
+        t,
+        [
+            hex(0x3f4909ea),
+            hex(0x3eba7cc2),
+            hex(0x3c9171e0),
+            hex(0xbab84ed8),
+            hex(0x391cda3e),
+            hex(0xb75ff7bf),
+        ],
+    );
+    let q = horner(t, [1., hex(0x3f4bbb93), hex(0x3e179862)]);
+    let mid = ((a + b) - ROOT.1) * p / q;
+    // ln z - 1/2z - s(1/z²)/z², s on [0, 1/9] with relative error 6e-8
+    let w = 1. / (z * z);
+    let s = horner(w, [hex(0x3daaaaaa), hex(0xbc088236), hex(0x3b7f4b68), hex(0xbb432c18)]);
+    let Df32(lh, ll) = ln_df(z);
+    let big = lh + (ll - fma(w, s, 0.5 / z));
+    let big = if z == f32::INFINITY { z } else { big };
+    let v = if z < 3. { mid } else { big };
+    // 1/y with its rounding error, which is most of the result near y = 1;
+    // the error term is NaN once 1/y overflows, -inf is the limit there
+    let r = 1. / y;
+    let re = if r < f32::INFINITY { fma(-r, y, 1.) * r } else { 0. };
+    let cot = PI.0 * cospi(x) / sinpi(x);
+    // the two 1/x cancel if both apply
+    let (d, dl) = if neg && small {
+        (cot, 0.)
+    } else if neg {
+        (cot + 1. / x, 0.)
+    } else if small {
+        (r, re)
+    } else {
+        (0., 0.)
+    };
+    let v = (v - d) - dl;
+    if x == 0. {
+        -1. / x
+    } else if x < 0. && Float::round(x) == x {
+        f32::NAN
+    } else {
+        v
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_values() {
+        let inf = f32::INFINITY;
+        assert_eq!(lgamma(1.), 0.);
+        assert_eq!(lgamma(2.), 0.);
+        assert_eq!(gamma(1.), 1.);
+        assert_eq!(gamma(2.), 1.);
+        for x in [0., -0., -1., -2., -100., -1e30, -inf, inf] {
+            assert_eq!(lgamma(x), inf, "{x}");
+        }
+        assert_eq!(lgamma(5e36), inf);
+        assert!(lgamma(4.085e36).is_finite());
+        assert_eq!(gamma(0.), inf);
+        assert_eq!(gamma(-0.), -inf);
+        assert_eq!(gamma(inf), inf);
+        for x in [36., 1e7, 1e9, f32::MAX] {
+            assert_eq!(gamma(x), inf, "{x}");
+        }
+        assert_eq!(gamma(-200.5), 0.);
+        assert_eq!(digamma(0.), -inf);
+        assert_eq!(digamma(-0.), inf);
+        assert_eq!(digamma(inf), inf);
+        // -1/x overflows below 2^-128
+        assert_eq!(digamma(1e-45), -inf);
+        assert_eq!(digamma(2e-39), -inf);
+        assert_eq!(digamma(-1e-45), inf);
+        for x in [-1., -2., -100., -1e30, -inf] {
+            assert!(gamma(x).is_nan(), "{x}");
+            assert!(digamma(x).is_nan(), "{x}");
+        }
+        for f in [lgamma, gamma, digamma] {
+            assert!(f(f32::NAN).is_nan());
+        }
+    }
+
+    #[test]
+    fn factorials() {
+        let mut f = 1f64;
+        for n in 1..35 {
+            let g = gamma(n as f32);
+            let ulps = (g.to_bits() as i64 - (f as f32).to_bits() as i64).abs();
+            assert!(ulps <= 1, "{n}: {g}");
+            f *= n as f64;
+        }
+    }
+
+    #[test]
+    fn signs() {
+        // Γ alternates in sign between the negative integers
+        for n in 0..30 {
+            let x = -(n as f32) - 0.5;
+            assert_eq!(gamma(x) < 0., n % 2 == 0, "{x}");
+            assert!(lgamma(x).is_finite());
+        }
+        assert!(gamma(-1e-3) < 0.);
+        assert!(gamma(-1.999) > 0.);
+    }
+}
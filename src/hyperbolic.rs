@@ -0,0 +1,184 @@
+//! sinh, cosh, tanh and their inverses on top of the `exp2` and `log_2`
+//! kernels. Odd minimax polynomials (from `remez`) take over near zero where
+//! the exponential/logarithm forms cancel, large arguments saturate to
+//! inf/±1 or switch to `ln(2x)`.
+
+use crate::doublefloat::consts::{LN_2, LOG2_E};
+use crate::poly::horner;
+use crate::scalar::{fma, hex, sqrt, Float};
+use crate::{exp2, log1p, log_2};
+
+/// e^x / 2 for 0 <= x <= 90, range reduced to |r| <= ln(2)/2 so `exp2` only
+/// sees [-0.5, 0.5], overflows to inf past 89.41.
+#[inline(always)]
+fn half_exp(x: f32) -> f32 {
+    let k = Float::round(x * LOG2_E.0);
+    let r = fma(-k, LN_2.0, x);
+    let r = fma(-k, LN_2.1, r);
+    // 2^(k-1) in two steps, k goes up to 130
+    let k = k as i32 - 1;
+    let (k1, k2) = (k / 2, k - k / 2);
+    exp2(r * LOG2_E.0) * f32::from_bits(((127 + k1) as u32) << 23) * f32::from_bits(((127 + k2) as u32) << 23)
+}
+
+#[inline(always)]
+fn ln(x: f32) -> f32 {
+    log_2(x) * LN_2.0
+}
+
+#[inline(always)]
+pub fn sinh(x: f32) -> f32 {
+    let a = x.abs();
+    if a < 1. {
+        // sinh on [0, 1], relative error 5.2e-9
+        let x2 = x * x;
+        let p = horner(x2, [hex(0x3e2aaaab), hex(0x3c08889a), hex(0x394fff49), hex(0x363d0ada)]);
+        fma(x * x2, p, x)
+    } else if a < 90. {
+        let h = half_exp(a);
+        (h - 0.25 / h).copysign(x)
+    } else {
+        // inf, or NaN
+        x * f32::INFINITY
+    }
+}
+
+#[inline(always)]
+pub fn cosh(x: f32) -> f32 {
+    // no cancellation anywhere, both terms are positive
+    let a = x.abs();
+    if a < 90. {
+        let h = half_exp(a);
+        h + 0.25 / h
+    } else {
+        a * f32::INFINITY
+    }
+}
+
+#[inline(always)]
+pub fn tanh(x: f32) -> f32 {
+    let a = x.abs();
+    if a < 0.55 {
+        // tanh on [0, 0.55], relative error 4.4e-9
+        let x2 = x * x;
+        let p = horner(x2, [hex(0xbeaaaaa5), hex(0x3e088693), hex(0xbd5c943d), hex(0x3cac9e84), hex(0xbbcd9823)]);
+        // p is negative, -0 would come out as +0
+        fma(a * x2, p, a).copysign(x)
+    } else if a < 9.1 {
+        // 1 - 2/(e^2a + 1), the subtracted term is at most 1/2
+        let e = half_exp(2. * a);
+        (1. - 1. / (e + 0.5)).copysign(x)
+    } else if a.is_nan() {
+        x
+    } else {
+        1f32.copysign(x)
+    }
+}
+
+// asinh for x >= 0
+#[inline(always)]
+fn asinh_pos(a: f32) -> f32 {
+    if a < 0.5 {
+        // asinh on [0, 0.5], relative error 2.1e-9
+        let a2 = a * a;
+        let p = horner(
+            a2,
+            [hex(0xbe2aaa9b), hex(0x3d999289), hex(0xbd35cc8a), hex(0x3ce6c2f0), hex(0xbc58e237)],
+        );
+        fma(a * a2, p, a)
+    } else if a < 4. {
+        // ln(1 + a + a^2/(1 + sqrt(a^2 + 1))), ln of the whole sum below
+        // loses up to 7 ulp just past 0.5, log1p of the part past 1 only 2
+        log1p(a + a * a / (1. + sqrt(fma(a, a, 1.))))
+    } else if a < 4096. {
+        ln(a + sqrt(fma(a, a, 1.)))
+    } else if a.is_finite() {
+        // sqrt(x^2 + 1) == x in f32 from here on
+        (log_2(a) + 1.) * LN_2.0
+    } else {
+        a
+    }
+}
+
+#[inline(always)]
+pub fn asinh(x: f32) -> f32 {
+    asinh_pos(x.abs()).copysign(x)
+}
+
+#[inline(always)]
+pub fn acosh(x: f32) -> f32 {
+    if x.is_nan() || x < 1. {
+        f32::NAN
+    } else if x < 2. {
+        // acosh(x) == asinh(sqrt(x^2 - 1)), x - 1 is exact and keeps the
+        // result accurate near 1 where ln(x + ...) would cancel
+        let t = x - 1.;
+        asinh_pos(sqrt(t * (t + 2.)))
+    } else if x < 4096. {
+        ln(x + sqrt(fma(x, x, -1.)))
+    } else if x.is_finite() {
+        (log_2(x) + 1.) * LN_2.0
+    } else {
+        x
+    }
+}
+
+#[inline(always)]
+pub fn atanh(x: f32) -> f32 {
+    let a = x.abs();
+    if a < 0.5 {
+        // atanh on [0, 0.5], relative error 4.7e-9
+        let x2 = x * x;
+        let p = horner(
+            x2,
+            [
+                hex(0x3eaaaaa0),
+                hex(0x3e4cd367),
+                hex(0x3e11a41e),
+                hex(0x3df22665),
+                hex(0x3d525d08),
+                hex(0x3e34ee88),
+            ],
+        );
+        fma(x * x2, p, x)
+    } else if a < 1. {
+        // 1 - a is exact here
+        (0.5 * ln((1. + a) / (1. - a))).copysign(x)
+    } else if a == 1. {
+        x * f32::INFINITY
+    } else {
+        f32::NAN
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn special_values() {
+        let inf = f32::INFINITY;
+        assert_eq!(sinh(100.), inf);
+        assert_eq!(sinh(-100.), -inf);
+        assert_eq!(sinh(89.4), 89.4f32.sinh());
+        assert_eq!(cosh(-inf), inf);
+        assert_eq!(tanh(20.), 1.);
+        assert_eq!(tanh(-inf), -1.);
+        assert_eq!(asinh(-inf), -inf);
+        // std's overflows in x^2 here
+        assert_eq!(asinh(f32::MAX), (f32::MAX as f64).asinh() as f32);
+        assert_eq!(acosh(1.), 0.);
+        assert_eq!(acosh(inf), inf);
+        assert!(acosh(0.5).is_nan());
+        assert_eq!(atanh(1.), inf);
+        assert_eq!(atanh(-1.), -inf);
+        assert!(atanh(1.5).is_nan());
+        for f in [sinh, cosh, tanh, asinh, acosh, atanh] {
+            assert!(f(f32::NAN).is_nan());
+        }
+        for f in [sinh, tanh, asinh, atanh] {
+            assert_eq!(f(-0.).to_bits(), (-0f32).to_bits());
+            assert_eq!(f(1e-30), 1e-30);
+        }
+    }
+}
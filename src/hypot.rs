@@ -0,0 +1,169 @@
+//! hypot, hypot3 and rhypot without overflow or underflow on the way. The
+//! components are scaled by a power of two from the exponent bits of the
+//! largest, so it lands in [1, 4) and no square over- or underflows that
+//! matters, squared exactly with `Df32::from_mul` and summed as a `Df32`.
+//! The square root of that gets one correction from its remainder, which
+//! leaves it correctly rounded except close to a halfway case, and never
+//! more than 1 ulp off. Undoing the scale is exact until the result is
+//! subnormal.
+
+use crate::scalar::{fma, sqrt};
+use crate::Df32;
+
+const EXPONENT_MASK: u32 = 0x7f800000;
+
+// 2^-e and 2^e for the exponent e of the biggest bit pattern given, with e
+// clamped so both are normal
+#[inline(always)]
+fn scales(bits: u32) -> (f32, f32) {
+    let e = (bits & EXPONENT_MASK).clamp(0x00800000, 0x7e800000);
+    (f32::from_bits(0x7f000000 - e), f32::from_bits(e))
+}
+
+// sqrt(h + l) as r + c, r the rounded square root of h
+#[inline(always)]
+fn sqrt_df(Df32(h, l): Df32) -> (f32, f32) {
+    let r = sqrt(h);
+    // h - r² is exact
+    let d = fma(-r, r, h) + l;
+    let c = if h > 0. { d * (0.5 / r) } else { 0. };
+    (r, c)
+}
+
+// 1/(r + c)
+#[inline(always)]
+fn recip(r: f32, c: f32) -> f32 {
+    let y = 1. / r;
+    // 1 - yr is exact
+    let e = fma(-y, r, 1.);
+    fma(y, fma(-c, y, e), y)
+}
+
+/// sqrt(x² + y²), inf if either is inf even if the other is NaN.
+#[inline(always)]
+pub fn hypot(x: f32, y: f32) -> f32 {
+    let (a, b) = (x.abs(), y.abs());
+    let (down, up) = scales(a.to_bits().max(b.to_bits()));
+    let (sa, sb) = (a * down, b * down);
+    let (r, c) = sqrt_df(Df32::from_mul(sa, sa) + Df32::from_mul(sb, sb));
+    let h = (r + c) * up;
+    if a == f32::INFINITY || b == f32::INFINITY {
+        f32::INFINITY
+    } else {
+        h
+    }
+}
+
+/// sqrt(x² + y² + z²), inf if any is inf even if another is NaN.
+#[inline(always)]
+pub fn hypot3(x: f32, y: f32, z: f32) -> f32 {
+    let (a, b, d) = (x.abs(), y.abs(), z.abs());
+    let (down, up) = scales(a.to_bits().max(b.to_bits()).max(d.to_bits()));
+    let (sa, sb, sd) = (a * down, b * down, d * down);
+    let s = Df32::from_mul(sa, sa) + Df32::from_mul(sb, sb) + Df32::from_mul(sd, sd);
+    let (r, c) = sqrt_df(s);
+    let h = (r + c) * up;
+    if a == f32::INFINITY || b == f32::INFINITY || d == f32::INFINITY {
+        f32::INFINITY
+    } else {
+        h
+    }
+}
+
+/// 1/sqrt(x² + y²), inf at the origin and 0 if either is inf.
+#[inline(always)]
+pub fn rhypot(x: f32, y: f32) -> f32 {
+    let (a, b) = (x.abs(), y.abs());
+    let (down, _) = scales(a.to_bits().max(b.to_bits()));
+    let (sa, sb) = (a * down, b * down);
+    let s = Df32::from_mul(sa, sa) + Df32::from_mul(sb, sb);
+    let (r, c) = sqrt_df(s);
+    let h = recip(r, c) * down;
+    if a == f32::INFINITY || b == f32::INFINITY {
+        0.
+    } else if s.0 == 0. {
+        f32::INFINITY
+    } else {
+        h
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_finite, ulps};
+
+    #[test]
+    fn special_values() {
+        let (inf, nan) = (f32::INFINITY, f32::NAN);
+        assert_eq!(hypot(3., 4.), 5.);
+        assert_eq!(hypot(-3e30, 4e30), 5e30);
+        assert_eq!(hypot(3e-30, -4e-30), 5e-30);
+        assert_eq!(hypot(3e38, 2e38), inf);
+        assert_eq!(hypot(3e-45, 4e-45), 5e-45);
+        assert_eq!(hypot(-0., -0.).to_bits(), 0);
+        assert_eq!(hypot(f32::MAX, 0.), f32::MAX);
+        assert_eq!(hypot(f32::from_bits(1), 0.), f32::from_bits(1));
+        assert_eq!(hypot(inf, nan), inf);
+        assert_eq!(hypot(nan, -inf), inf);
+        assert!(hypot(nan, 1.).is_nan());
+        assert_eq!(hypot3(2., -3., 6.), 7.);
+        assert_eq!(hypot3(2e37, 3e37, 6e37), 7e37);
+        assert_eq!(hypot3(nan, 1., inf), inf);
+        assert!(hypot3(1., nan, 1.).is_nan());
+        assert_eq!(rhypot(3., 4.), 0.2);
+        assert_eq!(rhypot(3e30, 4e30), 2e-31);
+        assert_eq!(rhypot(0., -0.), inf);
+        assert_eq!(rhypot(inf, nan), 0.);
+        assert_eq!(rhypot(f32::MAX, f32::MAX), (0.5f64.sqrt() / f32::MAX as f64) as f32);
+        assert!(rhypot(nan, 1.).is_nan());
+    }
+
+    #[test]
+    fn triples_are_exact() {
+        // the squares and their sum fit the Df32 exactly, the corrected
+        // root of a perfect square is the root itself, at any scale
+        for (a, b, c) in [(3., 4., 5.), (5., 12., 13.), (20., 21., 29.), (696., 697., 985.)] {
+            for k in [-140, -100, -20, 0, 20, 100, 124] {
+                let s = 2f64.powi(k) as f32;
+                assert_eq!(hypot(a * s, b * s), c * s, "{a} {b} {k}");
+            }
+        }
+        let s = 2f64.powi(-140) as f32;
+        assert_eq!(hypot3(1., 4., 8.), 9.);
+        assert_eq!(hypot3(2. * s, 6. * s, 9. * s), 11. * s);
+    }
+
+    #[test]
+    fn within_one_ulp() {
+        let mut state = 0x2545f491;
+        for _ in 0..200_000 {
+            let (x, y, z) = (
+                random_finite(&mut state),
+                random_finite(&mut state),
+                random_finite(&mut state),
+            );
+            // within a factor 16 of x, further apart y only shows in the
+            // last bits and the sample is about |x| again
+            let y = y.abs().min(x.abs() * 16.).max(x.abs() / 16.);
+            let z = z.clamp(-y, y);
+            let (xd, yd, zd) = (x as f64, y as f64, z as f64);
+            // squaring overflows or underflows f64 for the largest and
+            // smallest f32s
+            if (xd * xd).is_infinite() || xd * xd == 0. {
+                continue;
+            }
+            let expected = [
+                xd.hypot(yd) as f32,
+                (xd * xd + yd * yd + zd * zd).sqrt() as f32,
+                (1. / xd.hypot(yd)) as f32,
+            ];
+            let got = [hypot(x, y), hypot3(x, y, z), rhypot(x, y)];
+            for (g, e) in got.into_iter().zip(expected) {
+                if e.is_finite() && e != 0. {
+                    assert!(ulps(g, e) <= 1, "{x} {y} {z}: {g} {e}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,602 @@
+//! Guaranteed enclosures: every op on an [`Interval32`] returns an interval
+//! that contains the exact result for every point of its inputs.
+//!
+//! Arithmetic rounds outward using the exact tail of each endpoint, from
+//! the error-free `Df32` constructors, so an endpoint only moves one ulp
+//! when the rounding actually went the wrong way. The functions widen the
+//! kernel's result at the endpoints by its certified error bound, in ulps or
+//! absolute, whichever is tighter there (see `bound` and `cargo run
+//! --release --bin bounds`). Past the domain the bound was certified on, the
+//! value at the domain's edge still bounds one side and the function's
+//! natural range the other.
+
+use crate::accuracy::{from_ordered, ordered};
+use crate::Df32;
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Interval32 {
+    pub lo: f32,
+    pub hi: f32,
+}
+
+// below this a product's tail can underflow and stop being exact
+const TINY: f32 = 1e-30;
+
+// the exact value is s + e, round it down or up
+#[inline(always)]
+fn down(s: f32, e: f32) -> f32 {
+    if e < 0. {
+        s.next_down()
+    } else {
+        s
+    }
+}
+#[inline(always)]
+fn up(s: f32, e: f32) -> f32 {
+    if e > 0. {
+        s.next_up()
+    } else {
+        s
+    }
+}
+
+// an overflowed result with finite inputs is past MAX, not at infinity
+fn overflow(s: f32, lo: bool) -> f32 {
+    match (s, lo) {
+        (f32::INFINITY, true) => f32::MAX,
+        (f32::NEG_INFINITY, false) => f32::MIN,
+        _ => s,
+    }
+}
+
+fn add_round(a: f32, b: f32) -> (f32, f32) {
+    let Df32(s, e) = Df32::from_add(a, b);
+    if s.is_infinite() {
+        return match a.is_finite() && b.is_finite() {
+            true => (overflow(s, true), overflow(s, false)),
+            false => (s, s),
+        };
+    }
+    (down(s, e), up(s, e))
+}
+
+fn mul_round(a: f32, b: f32) -> (f32, f32) {
+    // 0 * inf is 0 here, the infinite end only stands for "unbounded"
+    if a == 0. || b == 0. {
+        return (0., 0.);
+    }
+    let Df32(p, e) = Df32::from_mul(a, b);
+    if p.is_infinite() {
+        return match a.is_finite() && b.is_finite() {
+            true => (overflow(p, true), overflow(p, false)),
+            false => (p, p),
+        };
+    }
+    if p.abs() < TINY {
+        return (p.next_down(), p.next_up());
+    }
+    (down(p, e), up(p, e))
+}
+
+fn div_round(a: f32, b: f32) -> (f32, f32) {
+    if a == 0. {
+        return (0., 0.);
+    }
+    let q = a / b;
+    if q.is_infinite() || b.is_infinite() {
+        return match a.is_finite() && b.is_finite() {
+            true => (overflow(q, true), overflow(q, false)),
+            false => (q, q),
+        };
+    }
+    if q.abs() < TINY || a.abs() < TINY {
+        return (q.next_down(), q.next_up());
+    }
+    // a/b = q + r/b exactly
+    let r = (-q).mul_add(b, a);
+    let e = if b < 0. { -r } else { r };
+    (down(q, e), up(q, e))
+}
+
+fn hull(bounds: impl IntoIterator<Item = (f32, f32)>) -> Interval32 {
+    bounds
+        .into_iter()
+        .fold(Interval32::EMPTY, |acc, (lo, hi)| Interval32 {
+            lo: acc.lo.min(lo),
+            hi: acc.hi.max(hi),
+        })
+}
+
+/// `n` representable f32s away from `v`, saturating at the infinities.
+fn step(v: f32, n: i64) -> f32 {
+    let inf = ordered(f32::INFINITY);
+    from_ordered((ordered(v) + n).clamp(-inf, inf))
+}
+
+// f64 to f32, rounded down or up
+fn below(y: f64) -> f32 {
+    let f = y as f32;
+    if f as f64 > y {
+        f.next_down()
+    } else {
+        f
+    }
+}
+fn above(y: f64) -> f32 {
+    let f = y as f32;
+    if (f as f64) < y {
+        f.next_up()
+    } else {
+        f
+    }
+}
+
+/// Where a kernel's certified bound holds.
+struct Certified {
+    f: fn(f32) -> f32,
+    ulps: i64,
+    /// bound on |f(x) - exact|, tighter than `ulps` near the zeros
+    abs: f64,
+    start: f32,
+    end: f32,
+}
+
+impl Certified {
+    // the correctly rounded result is within `ulps` of the kernel's, the
+    // exact one within one more, and within `abs` of it either way
+    fn widen(&self, x: f32) -> (f32, f32) {
+        let v = (self.f)(x);
+        let lo = step(v, -self.ulps - 1).max(below(v as f64 - self.abs));
+        let hi = step(v, self.ulps + 1).min(above(v as f64 + self.abs));
+        (lo, hi)
+    }
+    fn covers(&self, x: f32) -> bool {
+        self.start <= x && x <= self.end
+    }
+}
+
+// the max certified error over every f32 of each kernel's widest accuracy
+// domain, from `certified_exhaustive`, the abs ones rounded up. log_2 and
+// sin only have a relative bound away from their zeros
+const CBRT: Certified = Certified {
+    f: crate::cbrt,
+    ulps: 11,
+    abs: 0.621,
+    start: 1e-18,
+    end: 1e18,
+};
+const EXP2: Certified = Certified {
+    f: crate::exp2,
+    ulps: 4,
+    abs: 2.95e31,
+    start: -126.,
+    end: 127.,
+};
+const LOG_2: Certified = Certified {
+    f: crate::log_2,
+    ulps: 1763033842,
+    abs: 8.16e-6,
+    start: f32::MIN_POSITIVE,
+    end: f32::MAX,
+};
+const SIN: Certified = Certified {
+    f: crate::sin,
+    ulps: 1736441855,
+    abs: 1.26e-6,
+    start: -1000.,
+    end: 1000.,
+};
+const COS: Certified = Certified {
+    f: crate::cos,
+    ulps: 2592,
+    abs: 4.65e-7,
+    start: -1000.,
+    end: 1000.,
+};
+
+impl Interval32 {
+    pub const EMPTY: Self = Self {
+        lo: f32::INFINITY,
+        hi: f32::NEG_INFINITY,
+    };
+    pub const ENTIRE: Self = Self {
+        lo: f32::NEG_INFINITY,
+        hi: f32::INFINITY,
+    };
+
+    /// Panics if `lo > hi`. A NaN end could have been anything, so it gives
+    /// [`Self::ENTIRE`].
+    pub fn new(lo: f32, hi: f32) -> Self {
+        if lo.is_nan() || hi.is_nan() {
+            return Self::ENTIRE;
+        }
+        assert!(lo <= hi, "empty interval [{lo}, {hi}]");
+        Self { lo, hi }
+    }
+    /// [`Self::ENTIRE`] for NaN, like `new`.
+    pub const fn point(x: f32) -> Self {
+        match x.is_nan() {
+            true => Self::ENTIRE,
+            false => Self { lo: x, hi: x },
+        }
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.lo > self.hi
+    }
+
+    pub fn contains(self, x: f64) -> bool {
+        self.lo as f64 <= x && x <= self.hi as f64
+    }
+    pub fn width(self) -> f64 {
+        self.hi as f64 - self.lo as f64
+    }
+
+    pub fn sqrt(self) -> Self {
+        if self.hi < 0. || self.is_empty() {
+            return Self::EMPTY;
+        }
+        let root = |x: f32| {
+            let s = x.sqrt();
+            if s == 0. || s.is_infinite() {
+                return (s, s);
+            }
+            if x < TINY {
+                return (s.next_down(), s.next_up());
+            }
+            // sqrt(x) > s exactly when x > s*s
+            let r = (-s).mul_add(s, x);
+            (down(s, r), up(s, r))
+        };
+        Self {
+            lo: root(self.lo.max(0.)).0,
+            hi: root(self.hi).1,
+        }
+    }
+
+    // increasing functions, outside the certified domain the value at its
+    // edge still bounds one side and `natural` the other
+    fn increasing(self, c: &Certified, natural: Self) -> Self {
+        if self.is_empty() {
+            return Self::EMPTY;
+        }
+        let lo = match (self.lo < c.start, self.lo > c.end) {
+            (true, _) => natural.lo,
+            (_, true) => c.widen(c.end).0,
+            _ => c.widen(self.lo).0,
+        };
+        let hi = match (self.hi < c.start, self.hi > c.end) {
+            (true, _) => c.widen(c.start).1,
+            (_, true) => natural.hi,
+            _ => c.widen(self.hi).1,
+        };
+        Self {
+            lo: lo.max(natural.lo),
+            hi: hi.min(natural.hi),
+        }
+    }
+
+    pub fn exp2(self) -> Self {
+        self.increasing(&EXP2, Self::new(0., f32::INFINITY))
+    }
+
+    pub fn log_2(self) -> Self {
+        if self.hi < 0. || self.is_empty() {
+            return Self::EMPTY;
+        }
+        self.increasing(&LOG_2, Self::ENTIRE)
+    }
+
+    pub fn cbrt(self) -> Self {
+        if self.is_empty() {
+            return Self::EMPTY;
+        }
+        // odd, so the negative half is the mirror of the positive one
+        let half =
+            |lo: f32, hi: f32| Self { lo, hi }.increasing(&CBRT, Self::new(0., f32::INFINITY));
+        let mut parts = vec![];
+        if self.hi >= 0. {
+            let pos = half(self.lo.max(0.), self.hi);
+            parts.push((pos.lo, pos.hi));
+        }
+        if self.lo <= 0. {
+            let neg = half((-self.hi).max(0.), -self.lo);
+            parts.push((-neg.hi, -neg.lo));
+        }
+        hull(parts)
+    }
+
+    // `peak` is where the function is 1, a half period away it's -1
+    fn periodic(self, c: &Certified, peak: f64) -> Self {
+        if self.is_empty() {
+            return Self::EMPTY;
+        }
+        let unit = Self::new(-1., 1.);
+        if !c.covers(self.lo) || !c.covers(self.hi) || self.width() >= TAU {
+            return unit;
+        }
+        let (a, b) = (c.widen(self.lo), c.widen(self.hi));
+        let mut out = hull([a, b]);
+        // is there a k with lo <= at + 2kπ <= hi
+        let hits =
+            |at: f64| ((self.hi as f64 - at) / TAU).floor() >= ((self.lo as f64 - at) / TAU).ceil();
+        if hits(peak) {
+            out.hi = 1.;
+        }
+        if hits(peak + PI) {
+            out.lo = -1.;
+        }
+        Self {
+            lo: out.lo.max(-1.),
+            hi: out.hi.min(1.),
+        }
+    }
+
+    pub fn sin(self) -> Self {
+        self.periodic(&SIN, FRAC_PI_2)
+    }
+
+    pub fn cos(self) -> Self {
+        self.periodic(&COS, 0.)
+    }
+}
+
+impl From<f32> for Interval32 {
+    fn from(x: f32) -> Self {
+        Self::point(x)
+    }
+}
+
+impl Neg for Interval32 {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl Add for Interval32 {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        if self.is_empty() || rhs.is_empty() {
+            return Self::EMPTY;
+        }
+        Self {
+            lo: add_round(self.lo, rhs.lo).0,
+            hi: add_round(self.hi, rhs.hi).1,
+        }
+    }
+}
+
+impl Sub for Interval32 {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl Mul for Interval32 {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        if self.is_empty() || rhs.is_empty() {
+            return Self::EMPTY;
+        }
+        let (a, b) = (self, rhs);
+        hull([
+            mul_round(a.lo, b.lo),
+            mul_round(a.lo, b.hi),
+            mul_round(a.hi, b.lo),
+            mul_round(a.hi, b.hi),
+        ])
+    }
+}
+
+impl Div for Interval32 {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        if self.is_empty() || rhs.is_empty() {
+            return Self::EMPTY;
+        }
+        if rhs.contains(0.) {
+            return Self::ENTIRE;
+        }
+        let (a, b) = (self, rhs);
+        hull([
+            div_round(a.lo, b.lo),
+            div_round(a.lo, b.hi),
+            div_round(a.hi, b.lo),
+            div_round(a.hi, b.hi),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accuracy::{inputs, FUNCTIONS};
+    use crate::bound::{certify, KERNELS};
+    use crate::tune::Rng;
+
+    const CERTIFIED: [(&str, Certified); 5] = [
+        ("cbrt", CBRT),
+        ("exp2", EXP2),
+        ("log_2", LOG_2),
+        ("sin", SIN),
+        ("cos", COS),
+    ];
+
+    #[test]
+    fn bounds_are_certified() {
+        for (name, c) in CERTIFIED {
+            let kernel = KERNELS.iter().find(|k| k.name == name).unwrap();
+            let func = FUNCTIONS.iter().find(|f| f.name == name).unwrap();
+            let seg = func
+                .segments
+                .iter()
+                .find(|s| (s.start, s.end) == (c.start, c.end))
+                .unwrap();
+            let cert = certify(kernel, func.reference, inputs(seg, 20_000));
+            assert!(cert.max_ulp <= c.ulps as u64 && cert.max_abs <= c.abs, "{name}: {cert:?}");
+        }
+    }
+
+    // every f32 of each domain, about 15 minutes
+    // cargo test --release -- --ignored certified_exhaustive
+    #[test]
+    #[ignore]
+    fn certified_exhaustive() {
+        let mut ok = true;
+        for (name, c) in CERTIFIED {
+            let kernel = KERNELS.iter().find(|k| k.name == name).unwrap();
+            let func = FUNCTIONS.iter().find(|f| f.name == name).unwrap();
+            let every = (ordered(c.start)..=ordered(c.end)).map(from_ordered);
+            let cert = certify(kernel, func.reference, every);
+            println!("{name}: {cert:?}");
+            ok &= cert.max_ulp <= c.ulps as u64 && cert.max_abs <= c.abs;
+        }
+        assert!(ok);
+    }
+
+    // endpoints at every scale, with an occasional zero or exact integer
+    fn random_f32(rng: &mut Rng) -> f32 {
+        let x = match rng.next_u32() % 8 {
+            0 => 0.,
+            1 => (rng.next_u32() % 64) as f32,
+            _ => f32::from_bits(rng.next_u32() % 0x4f000000),
+        };
+        if rng.next_u32() & 1 == 0 {
+            -x
+        } else {
+            x
+        }
+    }
+
+    fn random_interval(rng: &mut Rng) -> Interval32 {
+        let (a, b) = (random_f32(rng), random_f32(rng));
+        // half of them narrow, around a
+        let b = if rng.next_u32() & 1 == 0 {
+            a + a * rng.next_f32() * 1e-3
+        } else {
+            b
+        };
+        Interval32::new(a.min(b), a.max(b))
+    }
+
+    // the ends and a few points inside
+    fn points(i: Interval32, rng: &mut Rng) -> Vec<f32> {
+        let mut xs = vec![i.lo, i.hi];
+        for _ in 0..4 {
+            xs.push((i.lo as f64 + i.width() * rng.next_f32() as f64) as f32);
+        }
+        xs.into_iter().filter(|&x| i.contains(x as f64)).collect()
+    }
+
+    #[test]
+    fn arithmetic_encloses() {
+        let mut rng = Rng::new(38);
+        for _ in 0..20_000 {
+            let (a, b) = (random_interval(&mut rng), random_interval(&mut rng));
+            let (sum, diff, prod, quot, root) = (a + b, a - b, a * b, a / b, a.sqrt());
+            for x in points(a, &mut rng) {
+                let x64 = x as f64;
+                if x >= 0. {
+                    assert!(root.contains(x64.sqrt()), "sqrt {a:?} at {x}: {root:?}");
+                }
+                for y in points(b, &mut rng) {
+                    let y64 = y as f64;
+                    // f64 is exact for sums and products of f32s that don't
+                    // leave its range, quotients round but never across an f32
+                    assert!(
+                        sum.contains(x64 + y64),
+                        "{a:?} + {b:?} at {x}, {y}: {sum:?}"
+                    );
+                    assert!(
+                        diff.contains(x64 - y64),
+                        "{a:?} - {b:?} at {x}, {y}: {diff:?}"
+                    );
+                    assert!(
+                        prod.contains(x64 * y64),
+                        "{a:?} * {b:?} at {x}, {y}: {prod:?}"
+                    );
+                    if y != 0. {
+                        assert!(
+                            quot.contains(x64 / y64),
+                            "{a:?} / {b:?} at {x}, {y}: {quot:?}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rounding_is_tight() {
+        // exact results stay points, inexact ones are one ulp wide
+        let one = Interval32::point(1.);
+        assert_eq!(one + one, Interval32::point(2.));
+        assert_eq!(one * Interval32::point(3.), Interval32::point(3.));
+        let third = one / Interval32::point(3.);
+        assert_eq!(third.hi, third.lo.next_up());
+        assert!(third.contains(1. / 3.));
+        assert_eq!(Interval32::point(4.).sqrt(), Interval32::point(2.));
+        let sum = one + Interval32::point(f32::EPSILON / 4.);
+        assert_eq!(sum, Interval32::new(1., 1f32.next_up()));
+    }
+
+    #[test]
+    fn functions_enclose() {
+        type Case = (fn(Interval32) -> Interval32, fn(f64) -> f64, &'static str);
+        let cases: [Case; 5] = [
+            (Interval32::exp2, f64::exp2, "exp2"),
+            (Interval32::log_2, f64::log2, "log_2"),
+            (Interval32::sin, f64::sin, "sin"),
+            (Interval32::cos, f64::cos, "cos"),
+            (Interval32::cbrt, f64::cbrt, "cbrt"),
+        ];
+        let mut rng = Rng::new(380);
+        for _ in 0..20_000 {
+            let i = random_interval(&mut rng);
+            // small intervals too, most of the random ones are huge
+            let small = Interval32::new(i.lo / 1e30, i.hi / 1e30);
+            let mid = Interval32::new(i.lo / 1e36, i.hi / 1e36);
+            for i in [i, small, mid] {
+                for (f, reference, name) in cases {
+                    let out = f(i);
+                    for x in points(i, &mut rng) {
+                        let y = reference(x as f64);
+                        if !y.is_nan() {
+                            assert!(out.contains(y), "{name} {i:?} at {x}: {y} not in {out:?}");
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn nan_is_entire() {
+        assert_eq!(Interval32::new(f32::NAN, 1.), Interval32::ENTIRE);
+        assert_eq!(Interval32::new(0., f32::NAN), Interval32::ENTIRE);
+        assert_eq!(Interval32::from(f32::NAN), Interval32::ENTIRE);
+        assert!(Interval32::from(f32::NAN).sqrt().contains(0.));
+    }
+
+    #[test]
+    fn function_edges() {
+        assert_eq!(Interval32::new(-3., -1.).log_2(), Interval32::EMPTY);
+        assert_eq!(Interval32::new(-3., -1.).sqrt(), Interval32::EMPTY);
+        assert_eq!(Interval32::new(0., 7.).sin(), Interval32::new(-1., 1.));
+        assert_eq!(Interval32::new(-1e30, 1e30).exp2().lo, 0.);
+        let c = Interval32::new(-8., 27.).cbrt();
+        assert!(c.contains(-2.) && c.contains(3.) && c.width() < 5.01);
+        // the peak inside forces the top to exactly 1
+        assert_eq!(Interval32::new(-0.5, 0.5).cos().hi, 1.);
+        // near a zero the absolute bound keeps sin and log_2 tight
+        assert!(Interval32::point(1e-20).sin().width() < 3e-6);
+        assert!(Interval32::point(1.).log_2().width() < 2e-5);
+    }
+}
@@ -0,0 +1,333 @@
+//! The kernels written once against [`Float`], so they can be traced and
+//! analyzed as well as evaluated. The public f32 functions in the crate
+//! root are these instantiated at `f32`.
+
+use crate::doublefloat::consts;
+use crate::scalar::{Bits, Float};
+
+const EXPONENT_MASK: u32 = 0x7f800000;
+const MANTISSA_MASK: u32 = 0x007fffff;
+
+#[inline(always)]
+fn fma<F: Float>(a: F, b: F, c: F) -> F {
+    a.mul_add(b, c)
+}
+#[inline(always)]
+fn c<F: Float>(v: f32) -> F {
+    F::lit(v)
+}
+#[inline(always)]
+fn hex<F: Float>(bits: u32) -> F {
+    F::lit(f32::from_bits(bits))
+}
+
+// the Df32 methods the kernels use, on (head, tail) pairs of F
+
+#[inline(always)]
+fn df_abs<F: Float>((hi, lo): (F, F)) -> (F, F) {
+    (hi.abs(), lo.mulsign(hi))
+}
+#[inline(always)]
+fn df_quick_add_to<F: Float>(a: (F, F), b: (F, F)) -> F {
+    let (s, e) = a.0.quick_two_sum(b.0);
+    s + (e + a.1 + b.1)
+}
+#[inline(always)]
+fn df_div_to<F: Float>(a: (F, F), b: (F, F)) -> F {
+    let rcp = c::<F>(1.0) / b.0;
+    let q1 = a.0 * rcp;
+    let rh = fma(-q1, b.0, a.0) + fma(-q1, b.1, a.1);
+    fma(rh, rcp, q1)
+}
+
+#[inline(always)]
+pub fn log_2<F: Float>(x: F) -> F {
+    let a = hex(0x40153ebb);
+    let b = hex(0x41163b4a);
+    let c = hex(0xc09c1a68);
+    let d = hex(0x3ecfca47);
+    let e = hex(0x409f8156);
+    let f = hex(0x40d76ca4);
+    let g = hex(0xc0dafb8a);
+    // log2(x*y) == log2(x)+log2(y)
+    let m = F::from_bits((x.to_bits() & MANTISSA_MASK) | 1_f32.to_bits());
+    let log2exponent =
+        F::from_bits(((x.to_bits() & EXPONENT_MASK) >> 8) | 256_f32.to_bits()) - self::c(383.);
+    let m2 = m * m;
+    log2exponent + fma(m2, fma(a, m, b), fma(g, m, c)) / fma(m2, fma(d, m, e), fma(f, m, self::c(1.)))
+}
+
+#[inline(always)]
+#[allow(clippy::excessive_precision, clippy::approx_constant)]
+pub fn exp2<F: Float>(x: F) -> F {
+    // exp2(floor(x))*exp2(fract(x)) == exp2(x)
+    let fl = x.floor();
+    let exp2int = F::from_bits(((fl + c(383.)).to_bits() << 8) & EXPONENT_MASK);
+    let f = x - fl;
+    let f2 = f * f;
+    fma(
+        fma(fma(c(2.1702255e-4), f, c(1.2439688e-3)), f, c(9.678841e-3)),
+        exp2int * f2 * f2,
+        fma(
+            fma(fma(c(5.5483342e-2), f, c(2.4022984e-1)), f, c(6.9314698e-1)),
+            exp2int * f,
+            exp2int,
+        ),
+    )
+}
+
+#[inline(always)]
+pub(crate) fn sinf_poly<F: Float>(x: F) -> F {
+    let a = hex(0xb2cc0ff1);
+    let b = hex(0x3638a80e);
+    let c: F = hex(0xb9500b44);
+    let d = hex(0x3c088883);
+    let e = hex(0xbe2aaaaa);
+    let x2 = x * x;
+    let x3 = x2 * x;
+    fma(
+        fma(fma(a, x2, b), x3, c * x),
+        x3 * x3,
+        fma(fma(d, x2, e), x3, x),
+    )
+}
+
+#[inline(always)]
+pub fn sin<F: Float>(x: F) -> F {
+    let (tau, rtau, hpi) = (consts::TAU, consts::FRAC_1_TAU, consts::FRAC_PI_2);
+    let q = c::<F>(0.25) - fma(x, c(rtau.0), c(0.25)).round();
+    let (y, e) = q.two_mul_add(c(tau.0), x);
+    let z = df_quick_add_to((c(-hpi.0), c(-hpi.1)), df_abs((y, fma(q, c(tau.1), e))));
+    sinf_poly(z)
+}
+
+#[inline(always)]
+pub fn cos<F: Float>(x: F) -> F {
+    let (tau, rtau, hpi) = (consts::TAU, consts::FRAC_1_TAU, consts::FRAC_PI_2);
+    let q = (x * c(rtau.0)).round();
+    let y = fma(q, c(tau.0), -x);
+    let (hi, lo) = df_abs((y, q * c(tau.1)));
+    let z = df_quick_add_to((c(hpi.0), c(hpi.1)), (-hi, -lo));
+    sinf_poly(z)
+}
+
+// The seeds and refinement steps of the cbrts on their own, `tune::SEEDS`
+// runs them with other constants and step counts.
+
+#[inline(always)]
+pub(crate) fn cbrt_seed<F: Float>(x: F, magic: u32) -> F {
+    F::from_bits(F::Bits::lit(magic).wrapping_add(x.to_bits() / 3))
+}
+
+// third order, from s to x^(1/3)
+#[inline(always)]
+pub(crate) fn cbrt_step<F: Float>(x: F, s: F) -> F {
+    let s2 = s * s;
+    fma(
+        fma(c::<F>(0.6) * s, s2, c::<F>(0.3) * x),
+        fma(s2, -s2, x * s) / fma(fma(s, s2, c::<F>(1.6) * x), s * s2, x * x * c(0.1)),
+        s,
+    )
+}
+
+// s for x^(1/3) and r for 1/(3 s²), the bits divided by 3 once for both
+#[inline(always)]
+pub(crate) fn cbrt_accurate_seed<F: Float>(x: F, magic: [u32; 2]) -> (F, F) {
+    let third = x.to_bits() / 3;
+    let s = F::from_bits(F::Bits::lit(magic[0]).wrapping_add(third));
+    let r = F::from_bits(F::Bits::lit(magic[1]).wrapping_sub(third << 1));
+    (s, r)
+}
+
+// s + r (x - s³), a Newton step with r for the inverse derivative kept
+// from the seed
+#[inline(always)]
+pub(crate) fn cbrt_accurate_step<F: Float>(x: F, s: F, r: F) -> F {
+    fma(s * s, s * -r, fma(r, x, s))
+}
+
+#[inline(always)]
+pub fn cbrt<F: Float>(x: F) -> F {
+    cbrt_step(x, cbrt_seed(x, 0x2a509a07))
+}
+
+#[inline(always)]
+pub fn cbrt_accurate<F: Float>(x: F) -> F {
+    let (s, r) = cbrt_accurate_seed(x, [0x2a4ddef1, 0x68ff2381]);
+    let s = cbrt_accurate_step(x, s, r);
+    let s = cbrt_accurate_step(x, s, r);
+    let s2 = s.two_prod(s);
+    let twos = s * c(2.);
+    let s32x = {
+        let b = fma(s2.0, twos, x);
+        let p = x - b;
+        let e = fma(s2.0, twos, p) - (p + b - x);
+        let lo = fma(s2.1, twos, e);
+        (b, lo)
+    };
+    let s2xps4 = {
+        let s40 = s2.0 * s2.0;
+        let e = fma(s2.0, s2.0, -s40);
+        let s41 = fma(s2.0 * c(2.), s2.1, fma(s2.1, s2.1, e));
+        let p = twos * x;
+        let e = fma(twos, x, -p);
+        let s = p + s40;
+        (s, s40 - (s - p) + e + s41)
+    };
+    df_div_to(s2xps4, s32x)
+}
+
+#[inline(always)]
+pub fn cbrt_throughput<F: Float>(x: F) -> F {
+    let r = F::from_bits(F::Bits::lit(0xd461ff81).wrapping_sub(x.to_bits() / 3));
+    let r2 = r * r;
+    let r = fma(r2, r2 * x, r * hex(0x3fb6e3d7));
+    let r2 = r * r;
+    let r = fma(r2, r2 * x, r * hex(0x3fe09c2a));
+    r * r * x
+}
+
+#[inline(always)]
+pub fn cbrt_fast<F: Float>(x: F) -> F {
+    let hi = x.to_bits() >> 16;
+    let s = F::from_bits(F::Bits::lit(0x2a4ddef1).wrapping_add(hi * 0x5556));
+    let r = F::from_bits(F::Bits::lit(0x68ff2381).wrapping_sub(hi * 0xaaac));
+    let s = cbrt_accurate_step(x, s, r);
+    cbrt_accurate_step(x, s, r)
+}
+
+#[cfg(test)]
+mod tests {
+    // the f32 kernels as they were before the generic rewrite, word for
+    // word but for formatting
+    mod baseline {
+        use crate::doublefloat::consts;
+        use crate::Df32;
+
+        const EXPONENT_MASK: u32 = 0x7f800000;
+        const MANTISSA_MASK: u32 = 0x007fffff;
+        const TAU: Df32 = consts::TAU;
+        const RTAU: Df32 = consts::FRAC_1_TAU;
+        const HPI: Df32 = consts::FRAC_PI_2;
+
+        fn fma(a: f32, b: f32, c: f32) -> f32 {
+            a.mul_add(b, c)
+        }
+
+        pub fn log_2(x: f32) -> f32 {
+            let a = f32::from_bits(0x40153ebb);
+            let b = f32::from_bits(0x41163b4a);
+            let c = f32::from_bits(0xc09c1a68);
+            let d = f32::from_bits(0x3ecfca47);
+            let e = f32::from_bits(0x409f8156);
+            let f = f32::from_bits(0x40d76ca4);
+            let g = f32::from_bits(0xc0dafb8a);
+            let m = f32::from_bits(1_f32.to_bits() | (x.to_bits() & MANTISSA_MASK));
+            let log2exponent = f32::from_bits(256_f32.to_bits() | ((x.to_bits() & EXPONENT_MASK) >> 8)) - 383.;
+            log2exponent + fma(m * m, fma(a, m, b), fma(g, m, c)) / fma(m * m, fma(d, m, e), fma(f, m, 1.))
+        }
+
+        fn sinf_poly(x: f32) -> f32 {
+            let a = f32::from_bits(0xb2cc0ff1);
+            let b = f32::from_bits(0x3638a80e);
+            let c = f32::from_bits(0xb9500b44);
+            let d = f32::from_bits(0x3c088883);
+            let e = f32::from_bits(0xbe2aaaaa);
+            let x2 = x * x;
+            let x3 = x2 * x;
+            fma(fma(fma(a, x2, b), x3, c * x), x3 * x3, fma(fma(d, x2, e), x3, x))
+        }
+
+        pub fn sin(x: f32) -> f32 {
+            let q = 0.25 - fma(x, RTAU.0, 0.25).round();
+            let y = q * TAU.0 + x;
+            let e = fma(q, TAU.0, x - y);
+            let z = (-HPI).quick_add_to_f32(Df32(y, fma(q, TAU.1, e)).abs());
+            sinf_poly(z)
+        }
+
+        pub fn cos(x: f32) -> f32 {
+            let q = (x * RTAU.0).round();
+            let y = fma(q, TAU.0, -x);
+            let z = HPI.quick_add_to_f32(-Df32(y, q * TAU.1).abs());
+            sinf_poly(z)
+        }
+
+        pub fn cbrt(x: f32) -> f32 {
+            let s = f32::from_bits(x.to_bits() / 3 + 0x2a509a07u32);
+            let s2 = s * s;
+            fma(
+                fma(0.6 * s, s2, 0.3 * x),
+                fma(s2, -s2, x * s) / fma(fma(s, s2, 1.6 * x), s * s2, x * x * 0.1),
+                s,
+            )
+        }
+
+        pub fn cbrt_accurate(x: f32) -> f32 {
+            let s = f32::from_bits(0x2a4ddef1u32.wrapping_add(x.to_bits() / 3));
+            let r = f32::from_bits(0x68ff2381u32.wrapping_sub((x.to_bits() / 3) << 1));
+            let s = fma(s * s, s * -r, fma(r, x, s));
+            let s = fma(s * s, s * -r, fma(r, x, s));
+            let s2 = Df32::from_mul(s, s);
+            let s32x = {
+                let b = fma(s2.0, s * 2., x);
+                let p = x - b;
+                let e = fma(s2.0, s * 2., p) - (p + b - x);
+                let lo = fma(s2.1, s * 2., e);
+                Df32(b, lo)
+            };
+            let s2xps4 = {
+                let s40 = s2.0 * s2.0;
+                let e = fma(s2.0, s2.0, -s40);
+                let s41 = fma(s2.0 * 2., s2.1, fma(s2.1, s2.1, e));
+                let p = s * 2. * x;
+                let e = fma(s * 2., x, -p);
+                let s = p + s40;
+                Df32(s, s40 - (s - p) + e + s41)
+            };
+            s2xps4.div_to_f32(s32x)
+        }
+
+        pub fn cbrt_throughput(x: f32) -> f32 {
+            let r = f32::from_bits(0xd461ff81u32.wrapping_sub(x.to_bits() / 3));
+            let r = fma(r * r, (r * r) * x, r * f32::from_bits(0x3fb6e3d7));
+            let r = fma(r * r, (r * r) * x, r * f32::from_bits(0x3fe09c2a));
+            r * r * x
+        }
+
+        pub fn cbrt_fast(x: f32) -> f32 {
+            let s = f32::from_bits(0x2a4ddef1u32.wrapping_add((x.to_bits() >> 16) * 0x5556u32));
+            let r = f32::from_bits(0x68ff2381u32.wrapping_sub((x.to_bits() >> 16) * 0xaaacu32));
+            let s = fma(s * s, s * -r, fma(r, x, s));
+            fma(s * s, s * -r, fma(r, x, s))
+        }
+    }
+
+    type Pair = (&'static str, fn(f32) -> f32, fn(f32) -> f32);
+    // exp2 left out, its integer part is rounded differently since on
+    // purpose, the golden hashes pin it from there on. cos negates the
+    // reduced pair's halves instead of the Df32, which must not change a bit
+    const PAIRS: [Pair; 7] = [
+        ("log_2", super::log_2, baseline::log_2),
+        ("sin", super::sin, baseline::sin),
+        ("cos", super::cos, baseline::cos),
+        ("cbrt", super::cbrt, baseline::cbrt),
+        ("cbrt_accurate", super::cbrt_accurate, baseline::cbrt_accurate),
+        ("cbrt_throughput", super::cbrt_throughput, baseline::cbrt_throughput),
+        ("cbrt_fast", super::cbrt_fast, baseline::cbrt_fast),
+    ];
+
+    fn same(a: f32, b: f32) -> bool {
+        a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+    }
+
+    #[test]
+    fn matches_baseline() {
+        for (name, generic, baseline) in PAIRS {
+            for bits in (0..=u32::MAX).step_by(4099) {
+                let x = f32::from_bits(bits);
+                assert!(same(generic(x), baseline(x)), "{name}({x:e})");
+            }
+        }
+    }
+}
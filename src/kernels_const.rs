@@ -0,0 +1,208 @@
+//! `const fn` copies of the kernels for building tables at compile time.
+//! The same op sequence as `kernels` on plain f32, with the core-only
+//! `soft` fma/floor/round, so the results are the same bits as the runtime
+//! functions (checked by the tests below). Keep the two in sync.
+
+use crate::doublefloat::consts;
+use crate::scalar::soft::{floor, mul_add as fma, round};
+
+const EXPONENT_MASK: u32 = 0x7f800000;
+const MANTISSA_MASK: u32 = 0x007fffff;
+const SIGN_MASK: u32 = 0x80000000;
+
+const fn hex(bits: u32) -> f32 {
+    f32::from_bits(bits)
+}
+
+const fn mulsign(x: f32, y: f32) -> f32 {
+    f32::from_bits(x.to_bits() ^ (y.to_bits() & SIGN_MASK))
+}
+
+const fn df_abs((hi, lo): (f32, f32)) -> (f32, f32) {
+    (hi.abs(), mulsign(lo, hi))
+}
+
+const fn df_quick_add_to(a: (f32, f32), b: (f32, f32)) -> f32 {
+    let s = a.0 + b.0;
+    let e = b.0 - (s - a.0);
+    s + (e + a.1 + b.1)
+}
+
+const fn df_div_to(a: (f32, f32), b: (f32, f32)) -> f32 {
+    let rcp = 1.0 / b.0;
+    let q1 = a.0 * rcp;
+    let rh = fma(-q1, b.0, a.0) + fma(-q1, b.1, a.1);
+    fma(rh, rcp, q1)
+}
+
+pub const fn log_2(x: f32) -> f32 {
+    let a = hex(0x40153ebb);
+    let b = hex(0x41163b4a);
+    let c = hex(0xc09c1a68);
+    let d = hex(0x3ecfca47);
+    let e = hex(0x409f8156);
+    let f = hex(0x40d76ca4);
+    let g = hex(0xc0dafb8a);
+    let m = f32::from_bits((x.to_bits() & MANTISSA_MASK) | 1_f32.to_bits());
+    let log2exponent =
+        f32::from_bits(((x.to_bits() & EXPONENT_MASK) >> 8) | 256_f32.to_bits()) - 383.;
+    let m2 = m * m;
+    log2exponent + fma(m2, fma(a, m, b), fma(g, m, c)) / fma(m2, fma(d, m, e), fma(f, m, 1.))
+}
+
+#[allow(clippy::excessive_precision, clippy::approx_constant)]
+pub const fn exp2(x: f32) -> f32 {
+    let fl = floor(x);
+    let exp2int = f32::from_bits(((fl + 383.).to_bits() << 8) & EXPONENT_MASK);
+    let f = x - fl;
+    let f2 = f * f;
+    fma(
+        fma(fma(2.1702255e-4, f, 1.2439688e-3), f, 9.678841e-3),
+        exp2int * f2 * f2,
+        fma(
+            fma(fma(5.5483342e-2, f, 2.4022984e-1), f, 6.9314698e-1),
+            exp2int * f,
+            exp2int,
+        ),
+    )
+}
+
+const fn sinf_poly(x: f32) -> f32 {
+    let a = hex(0xb2cc0ff1);
+    let b = hex(0x3638a80e);
+    let c = hex(0xb9500b44);
+    let d = hex(0x3c088883);
+    let e = hex(0xbe2aaaaa);
+    let x2 = x * x;
+    let x3 = x2 * x;
+    fma(
+        fma(fma(a, x2, b), x3, c * x),
+        x3 * x3,
+        fma(fma(d, x2, e), x3, x),
+    )
+}
+
+pub const fn sin(x: f32) -> f32 {
+    let (tau, rtau, hpi) = (consts::TAU, consts::FRAC_1_TAU, consts::FRAC_PI_2);
+    let q = 0.25 - round(fma(x, rtau.0, 0.25));
+    let y = q * tau.0 + x;
+    let e = fma(q, tau.0, x - y);
+    let z = df_quick_add_to((-hpi.0, -hpi.1), df_abs((y, fma(q, tau.1, e))));
+    sinf_poly(z)
+}
+
+pub const fn cos(x: f32) -> f32 {
+    let (tau, rtau, hpi) = (consts::TAU, consts::FRAC_1_TAU, consts::FRAC_PI_2);
+    let q = round(x * rtau.0);
+    let y = fma(q, tau.0, -x);
+    let (hi, lo) = df_abs((y, q * tau.1));
+    let z = df_quick_add_to((hpi.0, hpi.1), (-hi, -lo));
+    sinf_poly(z)
+}
+
+pub const fn cbrt(x: f32) -> f32 {
+    let s = f32::from_bits(x.to_bits() / 3 + 0x2a509a07);
+    let s2 = s * s;
+    fma(
+        fma(0.6 * s, s2, 0.3 * x),
+        fma(s2, -s2, x * s) / fma(fma(s, s2, 1.6 * x), s * s2, x * x * 0.1),
+        s,
+    )
+}
+
+pub const fn cbrt_accurate(x: f32) -> f32 {
+    let third = x.to_bits() / 3;
+    let s = f32::from_bits(0x2a4ddef1_u32.wrapping_add(third));
+    let r = f32::from_bits(0x68ff2381_u32.wrapping_sub(third << 1));
+    let s = fma(s * s, s * -r, fma(r, x, s));
+    let s = fma(s * s, s * -r, fma(r, x, s));
+    let s2 = (s * s, fma(s, s, -(s * s)));
+    let twos = s * 2.;
+    let s32x = {
+        let b = fma(s2.0, twos, x);
+        let p = x - b;
+        let e = fma(s2.0, twos, p) - (p + b - x);
+        let lo = fma(s2.1, twos, e);
+        (b, lo)
+    };
+    let s2xps4 = {
+        let s40 = s2.0 * s2.0;
+        let e = fma(s2.0, s2.0, -s40);
+        let s41 = fma(s2.0 * 2., s2.1, fma(s2.1, s2.1, e));
+        let p = twos * x;
+        let e = fma(twos, x, -p);
+        let s = p + s40;
+        (s, s40 - (s - p) + e + s41)
+    };
+    df_div_to(s2xps4, s32x)
+}
+
+pub const fn cbrt_throughput(x: f32) -> f32 {
+    let r = f32::from_bits(0xd461ff81_u32.wrapping_sub(x.to_bits() / 3));
+    let r2 = r * r;
+    let r = fma(r2, r2 * x, r * hex(0x3fb6e3d7));
+    let r2 = r * r;
+    let r = fma(r2, r2 * x, r * hex(0x3fe09c2a));
+    r * r * x
+}
+
+pub const fn cbrt_fast(x: f32) -> f32 {
+    let hi = x.to_bits() >> 16;
+    let s = f32::from_bits(0x2a4ddef1_u32.wrapping_add(hi * 0x5556));
+    let r = f32::from_bits(0x68ff2381_u32.wrapping_sub(hi * 0xaaac));
+    let s = fma(s * s, s * -r, fma(r, x, s));
+    fma(s * s, s * -r, fma(r, x, s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Pair = (&'static str, fn(f32) -> f32, fn(f32) -> f32);
+    const PAIRS: [Pair; 8] = [
+        ("log_2", log_2, crate::log_2),
+        ("exp2", exp2, crate::exp2),
+        ("sin", sin, crate::sin),
+        ("cos", cos, crate::cos),
+        ("cbrt", cbrt, crate::cbrt),
+        ("cbrt_accurate", cbrt_accurate, crate::cbrt_accurate),
+        ("cbrt_throughput", cbrt_throughput, crate::cbrt_throughput),
+        ("cbrt_fast", cbrt_fast, crate::cbrt_fast),
+    ];
+
+    fn same(a: f32, b: f32) -> bool {
+        a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan())
+    }
+
+    #[test]
+    fn matches_runtime() {
+        for (name, konst, runtime) in PAIRS {
+            for bits in (0..=u32::MAX).step_by(4099) {
+                let x = f32::from_bits(bits);
+                assert!(same(konst(x), runtime(x)), "{name}({x:e})");
+            }
+            // and densely where the range reduction boundaries are
+            for i in -100_000..100_000 {
+                let x = i as f32 * 1e-3;
+                assert!(same(konst(x), runtime(x)), "{name}({x})");
+            }
+        }
+    }
+
+    // evaluated by the compiler, not just callable as const
+    const TABLE: [f32; 4] = [exp2(0.5), log_2(10.), sin(1.), cbrt(27.)];
+
+    #[test]
+    fn const_table() {
+        assert_eq!(
+            TABLE.map(f32::to_bits),
+            [
+                crate::exp2(0.5),
+                crate::log_2(10.),
+                crate::sin(1.),
+                crate::cbrt(27.)
+            ]
+            .map(f32::to_bits)
+        );
+    }
+}
@@ -0,0 +1,201 @@
+//! Lambert W, the inverse of w e^w, on the principal branch w >= -1 and
+//! the branch w <= -1 below it. The two meet at x = -1/e with an infinite
+//! slope, W = -1 ± p - p²/3 ... in p = sqrt(2(ex + 1)), so x + 1/e is
+//! taken as a `Df32` and close to there W is that series. Elsewhere the
+//! start comes from `log_2` and two Halley steps on w - x e^-w, which has
+//! the root of w e^w - x and an `exp2` that can't overflow, take it to f32
+//! precision. The rounding of an f32 residual is divided by w + 1 though,
+//! so the last step is Newton's on w + ln|w| = ln|x| with the logs as
+//! `Df32`s. Selects rather than branches, so both vectorize.
+
+use crate::doublefloat::consts::LN_2;
+use crate::exp::exp_mul;
+use crate::gamma::ln_df;
+use crate::poly::horner;
+use crate::scalar::{fma, hex, sqrt};
+use crate::{log_2, Df32};
+
+const INV_E: Df32 = Df32::from_f64(1. / core::f64::consts::E);
+const TWO_E: f32 = 2. * core::f32::consts::E;
+const TWO_24: f32 = 16777216.;
+
+// x + 1/e, exact to well past f32 since x + 1/e rounded only cancels bits
+#[inline(always)]
+fn from_branch_point(x: f32) -> Df32 {
+    Df32::from_add(x, INV_E.0) + INV_E.1
+}
+
+// W = -1 + p - p²/3 + 11p³/72 ..., within 3e-10 for |p| < 0.15
+#[inline(always)]
+fn branch_series(p: f32) -> f32 {
+    let s = horner(
+        p,
+        [
+            -1. / 3.,
+            11. / 72.,
+            -43. / 540.,
+            769. / 17280.,
+            -221. / 8505.,
+            680863. / 43545600.,
+            -1963. / 204120.,
+        ],
+    );
+    fma(p * p, s, p) - 1.
+}
+
+// Halley on w - x e^-w, which is (w e^w - x)/e^w. e^-w only overflows on
+// the lower branch, where x brings it back in range, and x only does on
+// the principal one.
+#[inline(always)]
+fn halley(w: f32, x: f32, lower: bool) -> f32 {
+    let f = w - if lower {
+        exp_mul(-w, 0., x)
+    } else {
+        x * exp_mul(-w, 0., 1.)
+    };
+    let w1 = w + 1.;
+    w - f / (w1 - (w + 2.) * f / (2. * w1))
+}
+
+// Newton on w + ln|w| - ln|x|, whose derivative is (w + 1)/w. The logs
+// are good to about 3e-10, which the step divides by w + 1, so this is
+// only for away from the branch point.
+#[inline(always)]
+fn newton_ln(w: f32, a: f32) -> f32 {
+    let Df32(g, gl) = (ln_df(w.abs()) - ln_df(a)) + w;
+    fma(-(g + gl), w / (w + 1.), w)
+}
+
+/// The principal branch W0(x) >= -1 of the inverse of w e^w, for
+/// x >= -1/e and NaN below. Within 1 ulp.
+#[inline(always)]
+pub fn lambert_w0(x: f32) -> f32 {
+    let d = from_branch_point(x);
+    let p = sqrt(TWO_E * (d.0 + d.1));
+    // ln(1 + x)(1 - ln(1 + ln(1 + x))/(2 + ln(1 + x))), within 3% from
+    // -1/4 up
+    let l = log_2(1. + x) * LN_2.0;
+    let far = l * (1. - log_2(1. + l) * LN_2.0 / (2. + l));
+    let s = branch_series(p);
+    let w = if x < -0.25 { s } else { far };
+    let w = newton_ln(halley(halley(w, x, false), x, false), x.abs());
+    let w = if p < 0.15 { s } else { w };
+    // x - x² + 3x³/2 - 8x⁴/3 + 125x⁵/24, where ln|w| - ln|x| would cancel
+    let small = fma(x * horner(x, [-1., 1.5, -8. / 3., 125. / 24.]), x, x);
+    if x.abs() < hex(0x3b800000) {
+        small
+    } else if x == f32::INFINITY {
+        x
+    } else if d.0 < 0. {
+        f32::NAN
+    } else {
+        w
+    }
+}
+
+/// The lower branch W-1(x) <= -1 of the inverse of w e^w, for
+/// -1/e <= x < 0, -inf at 0 and NaN outside. Within 1 ulp.
+#[inline(always)]
+pub fn lambert_wm1(x: f32) -> f32 {
+    let d = from_branch_point(x);
+    let p = -sqrt(TWO_E * (d.0 + d.1));
+    // ln(-x) - ln(-ln(-x)) + ln(-ln(-x))/ln(-x), within 10% up to -1/4,
+    // subnormals scaled up for log_2
+    let a = -x;
+    let tiny = a < f32::MIN_POSITIVE;
+    let l1 = log_2(if tiny { a * TWO_24 } else { a });
+    let l1 = (if tiny { l1 - 24. } else { l1 }) * LN_2.0;
+    let l2 = log_2(-l1) * LN_2.0;
+    let far = l1 - l2 + l2 / l1;
+    let s = branch_series(p);
+    let w = if x < -0.25 { s } else { far };
+    let w = newton_ln(halley(halley(w, x, true), x, true), a);
+    let w = if p > -0.15 { s } else { w };
+    if x == 0. {
+        f32::NEG_INFINITY
+    } else if d.0 < 0. || x > 0. {
+        f32::NAN
+    } else {
+        w
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::ulps;
+
+    #[test]
+    fn special_values() {
+        let (inf, nan) = (f32::INFINITY, f32::NAN);
+        assert_eq!(lambert_w0(0.).to_bits(), 0);
+        assert_eq!(lambert_w0(-0.).to_bits(), (-0f32).to_bits());
+        assert_eq!(lambert_w0(1e-30), 1e-30);
+        assert_eq!(lambert_w0(inf), inf);
+        assert_eq!(lambert_wm1(0.), -inf);
+        assert_eq!(lambert_wm1(-0.), -inf);
+        // the f32s either side of -1/e
+        assert!(lambert_w0(hex(0xbebc5ab2)).is_nan());
+        assert!(lambert_wm1(hex(0xbebc5ab2)).is_nan());
+        assert!(lambert_w0(-inf).is_nan());
+        assert!(lambert_wm1(1e-30).is_nan());
+        assert!(lambert_wm1(-inf).is_nan());
+        assert!(lambert_w0(nan).is_nan());
+        assert!(lambert_wm1(nan).is_nan());
+    }
+
+    #[test]
+    fn within_one_ulp() {
+        // the root of w e^w - x lies between the neighbours of the result,
+        // w e^w being monotonic on each branch
+        let residual = |w: f32, x: f32| (w as f64) * (w as f64).exp() - x as f64;
+        for bits in (0..u32::MAX).step_by(1021) {
+            let x = f32::from_bits(bits);
+            if !x.is_finite() || x == 0. || x < -0.36787942 {
+                continue;
+            }
+            let branches: &[fn(f32) -> f32] = if x < 0. {
+                &[lambert_w0, lambert_wm1]
+            } else {
+                &[lambert_w0]
+            };
+            for f in branches {
+                let w = f(x);
+                let (a, b) = (f32::from_bits(w.to_bits() - 1), f32::from_bits(w.to_bits() + 1));
+                assert!(residual(a, x) * residual(b, x) <= 0., "{x}: {w}");
+            }
+        }
+        for i in 0..100000 {
+            let x = -0.36787942 + i as f32 * 1e-6;
+            for f in [lambert_w0, lambert_wm1] {
+                let w = f(x);
+                let (a, b) = (f32::from_bits(w.to_bits() - 1), f32::from_bits(w.to_bits() + 1));
+                assert!(residual(a, x) * residual(b, x) <= 0., "{x}: {w}");
+            }
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        // W(x) to 16 digits, and W e^W = x
+        let cases = [
+            (lambert_w0 as fn(f32) -> f32, 1., 0.5671432904097838),
+            (lambert_w0, 10., 1.7455280027406994),
+            (lambert_w0, 1e-3, 0.0009990015447412005),
+            (lambert_w0, 1e30, 64.90463378486527),
+            (lambert_w0, 3e38, 84.1640776255844),
+            (lambert_w0, -0.3, -0.4894022652671386),
+            (lambert_w0, -0.36, -0.8060844811504901),
+            (lambert_w0, hex(0xbebc5ab1), -0.9996649568388455),
+            (lambert_wm1, hex(0xbebc5ab1), -1.0003351180138207),
+            (lambert_wm1, -0.36, -1.2227699158678587),
+            (lambert_wm1, -0.3, -1.7813369328282314),
+            (lambert_wm1, -0.1, -3.57715204327411),
+            (lambert_wm1, -1e-30, -73.37311031060808),
+            (lambert_wm1, -1e-45, -107.96069714903614),
+        ];
+        for (f, x, w) in cases {
+            assert!(ulps(f(x), w as f32) <= 1, "{x}: {} {w}", f(x));
+        }
+    }
+}
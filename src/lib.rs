@@ -1,174 +1,129 @@
 // godbolt flags -C opt-level=3 -C target_feature=+fma
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-mod doublefloat;
-use doublefloat::Df32;
+#[cfg(feature = "std")]
+pub mod accuracy;
+mod activation;
+#[cfg(feature = "std")]
+pub mod bound;
+pub mod doublefloat;
+mod erf;
+mod exp;
+mod expm1;
+mod gamma;
+mod hyperbolic;
+mod hypot;
+#[cfg(feature = "std")]
+pub mod interval;
+pub mod kernels;
+pub mod kernels_const;
+mod lambert;
+pub mod poly;
+mod rootn;
+pub mod scalar;
+mod sum;
+#[cfg(test)]
+mod testing;
+#[cfg(feature = "std")]
+pub mod trace;
+#[cfg(feature = "std")]
+pub mod tune;
+mod trigpi;
+pub use activation::{
+    gelu, gelu_inplace, gelu_tanh, gelu_tanh_inplace, sigmoid, sigmoid_inplace, silu, silu_inplace,
+    softmax_inplace, softplus, softplus_inplace,
+};
+pub use doublefloat::Df32;
+pub use erf::{erf, erfc, erfinv};
+pub use expm1::{exp2m1, expm1, log1p, log2p1};
+pub use gamma::{digamma, gamma, lgamma, DIGAMMA_ROOT, EULER_GAMMA};
+pub use hyperbolic::{acosh, asinh, atanh, cosh, sinh, tanh};
+pub use hypot::{hypot, hypot3, rhypot};
+pub use lambert::{lambert_w0, lambert_wm1};
+pub use rootn::{pow_1_4, pow_2_3, pow_4_3, pow_m1_3, rootn};
+pub use sum::{dot_compensated, sum_compensated};
+pub use trigpi::{cos_turns, cospi, sin_turns, sinpi, tanpi};
 
-const SIGN_MASK: u32 = 0x80000000;
-const EXPONENT_MASK: u32 = 0x7f800000;
-const MANTISSA_MASK: u32 = 0x007fffff;
-use std::f32::consts::TAU as TAU32;
-use std::f64::consts::TAU as TAU64;
-const TAU: Df32 = Df32(TAU32, (TAU64 - (TAU32 as f64)) as f32);
-const RTAU: Df32 = Df32(
-    (1. / TAU64) as f32,
-    ((1. / TAU64) - (((1. / TAU64) as f32) as f64)) as f32,
-);
-const RPI: Df32 = Df32(RTAU.0 * 2., RTAU.1 * 2.);
-const HPI: Df32 = Df32(TAU.0 / 4., TAU.1 / 4.);
-const PI: Df32 = Df32(TAU.0 / 2., TAU.1 / 2.);
-
-#[inline(always)]
-fn fma(a: f32, b: f32, c: f32) -> f32 {
-    a.mul_add(b, c)
-}
-#[inline(always)]
-fn mulsign(x: f32, y: f32) -> f32 {
-    f32::from_bits(x.to_bits() ^ (y.to_bits() & SIGN_MASK))
-}
+// const versions for compile time tables, same bits as the ones below
+pub use kernels_const::{
+    cbrt as cbrt_const, cbrt_accurate as cbrt_accurate_const, cbrt_fast as cbrt_fast_const,
+    cbrt_throughput as cbrt_throughput_const, cos as cos_const, exp2 as exp2_const, log_2 as log_2_const,
+    sin as sin_const,
+};
 
 #[inline(always)]
 pub fn log_2(x: f32) -> f32 {
-    let a = f32::from_bits(0x40153ebb);
-    let b = f32::from_bits(0x41163b4a);
-    let c = f32::from_bits(0xc09c1a68);
-    let d = f32::from_bits(0x3ecfca47);
-    let e = f32::from_bits(0x409f8156);
-    let f = f32::from_bits(0x40d76ca4);
-    let g = f32::from_bits(0xc0dafb8a);
-    // log2(x*y) == log2(x)+log2(y)
-    let m = f32::from_bits(1_f32.to_bits() | (x.to_bits() & MANTISSA_MASK));
-    let log2exponent =
-        f32::from_bits(256_f32.to_bits() | ((x.to_bits() & EXPONENT_MASK) >> 8)) - 383.;
-    log2exponent + fma(m * m, fma(a, m, b), fma(g, m, c)) / fma(m * m, fma(d, m, e), fma(f, m, 1.))
+    kernels::log_2(x)
 }
 
 #[inline(always)]
 pub fn exp2(x: f32) -> f32 {
-    // exp2(floor(x))*exp2(fract(x)) == exp2(x)
-    let exp2int = f32::from_bits(((x + 383_f32).to_bits() << 8) & EXPONENT_MASK);
-    let f = x - x.floor();
-    fma(
-        fma(fma(2.1702255e-4, f, 1.2439688e-3), f, 9.678841e-3),
-        exp2int * (f * f) * (f * f),
-        fma(
-            fma(fma(5.5483342e-2, f, 2.4022984e-1), f, 6.9314698e-1),
-            exp2int * f,
-            exp2int,
-        ),
-    )
-}
-#[inline(always)]
-fn sinf_poly(x: f32) -> f32 {
-    let a = f32::from_bits(0xb2cc0ff1);
-    let b = f32::from_bits(0x3638a80e);
-    let c = f32::from_bits(0xb9500b44);
-    let d = f32::from_bits(0x3c088883);
-    let e = f32::from_bits(0xbe2aaaaa);
-    let x2 = x * x;
-    let x3 = x2 * x;
-    fma(
-        fma(fma(a, x2, b), x3, c * x),
-        x3 * x3,
-        fma(fma(d, x2, e), x3, x),
-    )
-    //fma(fma(fma(fma(fma(a, x2, b), x2, c), x2, d), x2, e), x2*x, x)
+    kernels::exp2(x)
 }
+
 #[inline(always)]
 pub fn sin(x: f32) -> f32 {
-    let q = 0.25 - fma(x, RTAU.0, 0.25).round();
-    let y = q * TAU.0 + x;
-    let e = fma(q, TAU.0, x - y);
-    let z = (-HPI).quick_add_to_f32(Df32(y, fma(q, TAU.1, e)).abs());
-    sinf_poly(z)
+    kernels::sin(x)
 }
+
 #[inline(always)]
 pub fn cos(x: f32) -> f32 {
-    let q = (x * RTAU.0).round();
-    let y = fma(q, TAU.0, -x);
-    let z = HPI.quick_add_to_f32(-Df32(y, q * TAU.1).abs());
-    sinf_poly(z)
+    kernels::cos(x)
 }
 
 #[inline(always)]
 pub fn cbrt(x: f32) -> f32 {
-    let s = f32::from_bits(x.to_bits() / 3 + 0x2a509a07u32);
-    let s2 = s * s;
-    fma(
-        fma(0.6 * s, s2, 0.3 * x),
-        fma(s2, -s2, x * s) / fma(fma(s, s2, 1.6 * x), s * s2, x * x * 0.1),
-        s,
-    )
+    kernels::cbrt(x)
 }
 
 #[inline(always)]
 pub fn cbrt_accurate(x: f32) -> f32 {
-    let s = f32::from_bits(0x2a4ddef1u32.wrapping_add(x.to_bits() / 3));
-    let r = f32::from_bits(0x68ff2381u32.wrapping_sub((x.to_bits() / 3) << 1));
-    let s = fma(s * s, s * -r, fma(r, x, s));
-    let s = fma(s * s, s * -r, fma(r, x, s));
-    let s2 = Df32::from_mul(s, s);
-    let s32x = {
-        let b = fma(s2.0, s * 2., x);
-        let p = x - b;
-        let e = fma(s2.0, s * 2., p) - (p + b - x);
-        let lo = fma(s2.1, s * 2., e);
-        Df32(b, lo)
-    };
-    let s2xps4 = {
-        let s40 = s2.0 * s2.0;
-        let e = fma(s2.0, s2.0, -s40);
-        let s41 = fma(s2.0 * 2., s2.1, fma(s2.1, s2.1, e));
-        let p = s * 2. * x;
-        let e = fma(s * 2., x, -p);
-        let s = p + s40;
-        Df32(s, s40 - (s - p) + e + s41)
-    };
-    return s2xps4.div_to_f32(s32x);
+    kernels::cbrt_accurate(x)
 }
 
 // higher throughput cbrt experiment, 5.5 ulp average error
-fn cbrt_throughput(x: f32) -> f32 {
-    //let r = f32::from_bits(0xd461ff81u32.wrapping_sub((x.to_bits()>>16)*0x5556u32));
-    let r = f32::from_bits(0xd461ff81u32.wrapping_sub(x.to_bits() / 3));
-    let r = fma(r * r, (r * r) * x, r * f32::from_bits(0x3fb6e3d7));
-    let r = fma(r * r, (r * r) * x, r * f32::from_bits(0x3fe09c2a));
-    r * r * x
+#[inline(always)]
+pub fn cbrt_throughput(x: f32) -> f32 {
+    kernels::cbrt_throughput(x)
 }
 
 
+#[allow(dead_code)]
 fn cbrt_approx(x: f32) -> f32 {
 	let y = f32::from_bits(0x2a509849u32 + (x.to_bits() / 3));
 	let y = (x + 2.*(y*y)*y) / (3.*(y*y));
     (2.*x*y + (y*y)*(y*y))/(x + 2.*(y*y)*y)
     //(x + 2.*(y*y)*y) / (3.*(y*y))
 }
+#[allow(dead_code)]
 fn sqrt_approx(x: f32) -> f32 {
     f32::from_bits(0x1FBD22DF + (x.to_bits() >> 1))
 }
+#[allow(dead_code)]
 fn rcp_approx(x: f32) -> f32 {
     f32::from_bits(0x7EEF370B - x.to_bits())
 }
+#[allow(dead_code)]
 fn exp2_approx(x: f32) -> f32 {
     -f32::from_bits((x + 383.).to_bits() << 8)
 }
+#[allow(dead_code)]
 fn log2_approx(x: f32) -> f32 {
     f32::from_bits((x).to_bits() >> 8 | 256_f32.to_bits()) - 383.
 }
+#[allow(dead_code)]
 fn rsqrt_approx(x: f32) -> f32 {
     f32::from_bits(0x5F33E79F - (x.to_bits() >> 1))
 }
 
 // 50 average ulp error 32 cycle latency 5.5 cycle rthroughput
-fn cbrt_fast(x: f32) -> f32 {
-    let s = f32::from_bits(0x2a4ddef1u32.wrapping_add((x.to_bits()>>16)*0x5556u32));
-    let r = f32::from_bits(0x68ff2381u32.wrapping_sub((x.to_bits()>>16)*0xaaacu32));
-    let s = fma(s * s, s * -r, fma(r, x, s));
-    fma(s * s, s * -r, fma(r, x, s))
+#[inline(always)]
+pub fn cbrt_fast(x: f32) -> f32 {
+    kernels::cbrt_fast(x)
 }
 pub fn cbrt_constant(x: f32, c: &[u32]) -> f32 {
 	let y = f32::from_bits(c[0] + (x.to_bits() / 3));
-	let y = (x + 2.*(y*y)*y) / (3.*(y*y));
-    y
+	(x + 2.*(y*y)*y) / (3.*(y*y))
 
     //h4
     //s - ((s3 - x)*(Df32(s3x.0*16.,s3x.1*16.).quick_add_df(10.*s6) + x2)).div_to_f32(
@@ -195,119 +150,42 @@ pub fn cbrt_constant(x: f32, c: &[u32]) -> f32 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::Rng;
-    use rand::RngExt;
-
-    fn run_descent(
-        f: impl Fn(f32, &[u32]) -> f32,
-        reference: impl Fn(f32) -> f32,
-        initial_consts: &[u32],
-    ) {
-        let mut consts: Vec<u32> = initial_consts.to_vec();
-        let iters = 10_000;
 
-        let mut best_err: u64 = 0;
-        let mut steps: u64 = 0;
-        for _ in 1..iters {
-            let x: f32 = rand::rng().random::<f32>().abs();
-            let result = f(x, &consts);
-            best_err += reference(x).to_bits().abs_diff(result.to_bits()) as u64;
-        }
-        let mut tryagain = false;
-        println!(
-            "optimizing! starting error: {}",
-            best_err as f64 / iters as f64
-        );
-        let mut deltas: Vec<u32> = consts
-            .iter()
-            .map(|&_| {
-                let n: u32 = rand::rng().random();
-                f32::from_bits(n) as i32 as u32
-            })
-            .collect();
-        loop {
-            let mut new_err: u64 = 0;
-            let mut old_err: u64 = 0;
-            if !tryagain {
-                deltas = consts
-                    .iter()
-                    .map(|&_| {
-                        let n: u32 = rand::rng().random();
-                        //return f32::from_bits(n) as i32 as u32;
-                        if steps < 10 {
-                            n
-                        } else {
-                            if steps & 1 == 0 {
-                                n
-                            } else {
-                                f32::from_bits(n) as i32 as u32
-                            }
-                        }
-                    })
-                    .collect();
-            }
-            steps += 1;
-            let new_consts: Vec<u32> = consts
-                .iter()
-                .zip(deltas.iter())
-                .map(|(&c, &d)| c.wrapping_add(d))
-                .collect();
-            if new_consts.iter().zip(consts.iter()).all(|(&x, &y)| x == y) {
-                continue;
-            }
-            for _ in 1..iters {
-                let x: f32 = rand::rng().random::<f32>().abs();
-                let ref_val = reference(x);
-                let old_result = f(x, &consts);
-                let new_result = f(x, &new_consts);
-                new_err += ref_val.to_bits().abs_diff(new_result.to_bits()) as u64;
-                old_err += ref_val.to_bits().abs_diff(old_result.to_bits()) as u64;
-            }
-            if new_err <= old_err {
-                consts = new_consts.clone();
-                if new_err < best_err {
-                    let mut new_err_nomul: u64 = 0;
-                    // no mul check
-                    for _ in 1..iters {
-                        let x: f32 = rand::rng().random::<f32>().abs();
-                        let new_result = f(x, &new_consts);
-                        new_err_nomul +=
-                            reference(x).to_bits().abs_diff(new_result.to_bits()) as u64;
-                    }
-                    if new_err_nomul < best_err {
-                        best_err = new_err_nomul;
-                        let const_strs: Vec<String> =
-                            consts.iter().map(|c| format!("0x{:x}u32", c)).collect();
-                        println!(
-                            "new best consts {} with error {} nomul: {} step:{}",
-                            const_strs.join(", "),
-                            new_err as f64 / iters as f64,
-                            new_err_nomul as f64 / iters as f64,
-                            steps
-                        );
-                    }
-                    tryagain = true;
-                } else {
-                    tryagain = false;
-                }
-            } else {
-                tryagain = false;
-            }
-            if new_err == best_err {
-                best_err = new_err;
-                consts = new_consts;
-            }
-        }
-    }
-    /*
+    // cargo test --release -- --ignored descent2
     #[test]
+    #[ignore]
+    #[cfg(feature = "std")]
     fn descent2() {
-        run_descent(
-            |x, consts| cbrt_constant(x, consts),
+        let inputs = tune::uniform_inputs(0, 0.0..1.0, 10_000);
+        let opts = tune::Options {
+            budget: tune::Budget::Time(std::time::Duration::from_secs(600)),
+            output: Some("descent2.txt".into()),
+            verbose: true,
+            ..Default::default()
+        };
+        tune::descend(
+            cbrt_constant,
             |x| (x as f64).cbrt() as f32,
+            &inputs,
             &[0x2a5063f7],
+            &opts,
         );
-    }*/
+    }
+
+    // the kernels only need core, `NO_STD_TARGET=thumbv7em-none-eabihf` to
+    // also cross-check on a target without std
+    #[test]
+    fn builds_without_std() {
+        let mut cargo = std::process::Command::new(env!("CARGO"));
+        cargo
+            .args(["build", "--lib", "--quiet", "--no-default-features"])
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .env("CARGO_TARGET_DIR", concat!(env!("CARGO_MANIFEST_DIR"), "/target/no_std"));
+        if let Some(target) = std::env::var_os("NO_STD_TARGET") {
+            cargo.arg("--target").arg(target);
+        }
+        assert!(cargo.status().unwrap().success());
+    }
 
     #[test]
     fn it_works() {
@@ -455,10 +333,10 @@ mod tests {
             .legend(|(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], RED));
         chart.configure_series_labels().draw().unwrap();
         root.present().expect("Unable to write result to file");
-        std::process::Command::new("pngquant")
+        // best effort, pngquant isn't always installed
+        let _ = std::process::Command::new("pngquant")
             .args(["--force", "--ext", ".png", "16", "--", path])
-            .status()
-            .unwrap();
+            .status();
     }
 
     fn plot_error(path: &str, x_start: f32, x_end: f32, f: impl Fn(f32) -> f32) {
@@ -491,10 +369,10 @@ mod tests {
             .unwrap();
         chart.draw_series(LineSeries::new(samples, &BLACK)).unwrap();
         root.present().expect("Unable to write result to file");
-        std::process::Command::new("pngquant")
+        // best effort, pngquant isn't always installed
+        let _ = std::process::Command::new("pngquant")
             .args(["--force", "--ext", ".png", "16", "--", path])
-            .status()
-            .unwrap();
+            .status();
     }
 
     #[test]
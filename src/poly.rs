@@ -0,0 +1,112 @@
+//! Polynomial evaluation schemes. Coefficients are in ascending order,
+//! `c[0] + c[1]*x + c[2]*x^2 + ...`, the same order `remez` prints them in.
+
+use crate::doublefloat::{two_sum, Df32};
+use crate::scalar::Float;
+
+#[inline(always)]
+fn fma(a: f32, b: f32, c: f32) -> f32 {
+    Float::mul_add(a, b, c)
+}
+
+/// Horner's scheme, one fma per coefficient. Fewest operations and most
+/// accurate, but the longest dependency chain, every fma waits on the last.
+#[inline(always)]
+pub fn horner<const N: usize>(x: f32, c: [f32; N]) -> f32 {
+    let mut acc = 0.;
+    for i in (0..N).rev() {
+        acc = fma(acc, x, c[i]);
+    }
+    acc
+}
+
+/// Estrin's scheme, pairs of coefficients are combined with x, then pairs
+/// of those with x², x⁴... Latency grows with log2(N) instead of N.
+#[inline(always)]
+pub fn estrin<const N: usize>(x: f32, c: [f32; N]) -> f32 {
+    if N == 0 {
+        return 0.;
+    }
+    let mut c = c;
+    let mut x = x;
+    let mut n = N;
+    while n > 1 {
+        for i in 0..n / 2 {
+            c[i] = fma(c[2 * i + 1], x, c[2 * i]);
+        }
+        if n % 2 == 1 {
+            c[n / 2] = c[n - 1];
+        }
+        n = n.div_ceil(2);
+        x *= x;
+    }
+    c[0]
+}
+
+/// Compensated Horner (Graillat, Langlois, Louvet). The rounding error of
+/// every step is captured with `Df32::from_mul` and a two-sum and carried
+/// along in a second Horner chain, so the result is as accurate as plain
+/// Horner in twice the working precision.
+#[inline(always)]
+pub fn horner_compensated<const N: usize>(x: f32, c: [f32; N]) -> Df32 {
+    if N == 0 {
+        return Df32::from_f32(0.);
+    }
+    let mut s = c[N - 1];
+    let mut lo = 0.;
+    for i in (0..N - 1).rev() {
+        let p = Df32::from_mul(s, x);
+        let (hi, e) = two_sum(p.0, c[i]);
+        s = hi;
+        lo = fma(lo, x, p.1 + e);
+    }
+    Df32::from_quick_add(s, lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn horner64(x: f64, c: &[f32]) -> f64 {
+        c.iter().rev().fold(0., |acc, &c| acc * x + c as f64)
+    }
+
+    #[test]
+    fn schemes_agree() {
+        // taylor series of exp
+        let c = [1., 1., 1. / 2., 1. / 6., 1. / 24., 1. / 120., 1. / 720.];
+        for i in 0..=100 {
+            let x = i as f32 / 100.;
+            let exact = horner64(x as f64, &c);
+            let ulp = (exact as f32).to_bits();
+            assert!(horner(x, c).to_bits().abs_diff(ulp) <= 1);
+            assert!(estrin(x, c).to_bits().abs_diff(ulp) <= 1);
+            assert_eq!(horner_compensated(x, c).to_f32(), exact as f32);
+        }
+        assert_eq!(horner(2., [] as [f32; 0]), 0.);
+        assert_eq!(estrin(2., [] as [f32; 0]), 0.);
+        assert_eq!(estrin(2., [3.]), 3.);
+        assert_eq!(estrin(2., [3., 1., 1.]), 9.);
+        assert_eq!(estrin(2., [1., 1., 1., 1., 1.]), 31.);
+    }
+
+    #[test]
+    fn compensated_ill_conditioned() {
+        // (x-1)^7 expanded, condition numbers of 1e5 to 1e11 near the root
+        let c = [-1., 7., -21., 35., -35., 21., -7., 1.];
+        let mut naive_err: f64 = 0.;
+        let mut comp_err: f64 = 0.;
+        for i in (-150..-50).chain(50..150) {
+            let x = 1. + i as f32 / 1000.;
+            let exact = (x as f64 - 1.).powi(7);
+            let naive = horner(x, c) as f64;
+            let comp = horner_compensated(x, c);
+            let comp = comp.0 as f64 + comp.1 as f64;
+            naive_err = naive_err.max(((naive - exact) / exact).abs());
+            comp_err = comp_err.max(((comp - exact) / exact).abs());
+        }
+        assert!(naive_err > 0.1, "{naive_err}");
+        // expected error is u + cond * u^2
+        assert!(comp_err < 1e-3, "{comp_err}");
+    }
+}
@@ -0,0 +1,265 @@
+//! rootn and the fixed rational powers. x^(1/4), x^(2/3), x^(-1/3) and
+//! x^(4/3) start like `cbrt_accurate`, from a seed for x^(-1/q) that
+//! divides the bits by q and subtracts them from a constant tuned with
+//! `magic`, refined by one third order step in f32. The last step is third
+//! order too, in the residual 1 - x r^q, which the `Df32` products of x and
+//! r that make up the power give exactly enough, so they're within 1 ulp
+//! and nearly always correctly rounded. Small inputs are scaled up by a
+//! power of two both q divide. rootn takes any n, where r^n could overflow,
+//! so it's e^(ln|x|/n) with the log as a `Df32` instead, and a Newton step
+//! on the log of the result makes it as accurate.
+
+use crate::exp::exp_mul;
+use crate::gamma::ln_df;
+use crate::scalar::{fma, hex};
+use crate::Df32;
+
+const TWO_24: f32 = 16777216.;
+
+// scales subnormal x up by 2^24, the seeds only work on normal numbers
+#[inline(always)]
+fn unsubnormal(a: f32) -> (f32, bool) {
+    let tiny = a < f32::MIN_POSITIVE;
+    (if tiny { a * TWO_24 } else { a }, tiny)
+}
+
+// The seeds for x^(-1/3) and x^(-1/4) and their first steps, apart so
+// `tune::SEEDS` can run them with other constants and step counts.
+
+#[inline(always)]
+pub(crate) fn rcbrt_seed(x: f32, magic: u32) -> f32 {
+    hex(magic.wrapping_sub(x.to_bits() / 3))
+}
+
+// r (1 - e)^(-1/3) to third order, e = 1 - x r³
+#[inline(always)]
+pub(crate) fn rcbrt_step(x: f32, r: f32) -> f32 {
+    let e = fma(-(x * r) * r, r, 1.);
+    fma(r, e * fma(e, 2. / 9., 1. / 3.), r)
+}
+
+#[inline(always)]
+pub(crate) fn rqrt_seed(x: f32, magic: u32) -> f32 {
+    hex(magic.wrapping_sub(x.to_bits() / 4))
+}
+
+// r (1 - e)^(-1/4) to third order, e = 1 - x r⁴
+#[inline(always)]
+pub(crate) fn rqrt_step(x: f32, r: f32) -> f32 {
+    let r2 = r * r;
+    let e = fma(-(x * r2), r2, 1.);
+    fma(r, e * fma(e, 5. / 32., 0.25), r)
+}
+
+// y (1 - e)^-a for the power y of x and r, to third order with c1 = a,
+// c2 = a(a + 1)/2 and c3 = a(a + 1)(a + 2)/6
+#[inline(always)]
+fn correct(Df32(h, l): Df32, e: f32, [c1, c2, c3]: [f32; 3]) -> f32 {
+    h + fma(h, e * fma(e, fma(e, c3, c2), c1), l)
+}
+
+// x^(-1/3) as r and x r, x r² as `Df32`s, and 1 - x r³
+#[inline(always)]
+fn cbrt_parts(a: f32) -> (f32, Df32, Df32, f32) {
+    // within 1.9e-4 for normal a
+    let r = rcbrt_step(a, rcbrt_seed(a, 0x54a21409));
+    let p1 = Df32::from_mul(a, r);
+    let p2 = p1 * r;
+    let Df32(h, l) = p2 * r;
+    // 1 - h is exact
+    (r, p1, p2, (1. - h) - l)
+}
+
+/// x^(1/4), NaN for x < 0. Within 1 ulp.
+#[inline(always)]
+pub fn pow_1_4(x: f32) -> f32 {
+    let (a, tiny) = unsubnormal(x.abs());
+    // within 2.3e-4 for normal a
+    let r = rqrt_step(a, rqrt_seed(a, 0x4f583c29));
+    let p3 = Df32::from_mul(a, r) * r * r;
+    let Df32(h, l) = p3 * r;
+    let e = (1. - h) - l;
+    let y = correct(p3, e, [3. / 4., 21. / 32., 77. / 128.]);
+    let y = if tiny { y * hex(0x3c800000) } else { y };
+    if x < 0. {
+        f32::NAN
+    } else if a == f32::INFINITY || a == 0. {
+        a
+    } else {
+        y
+    }
+}
+
+/// x^(2/3), cbrt(x)² with the real cube root, so even for x < 0. Within
+/// 1 ulp.
+#[inline(always)]
+pub fn pow_2_3(x: f32) -> f32 {
+    let (a, tiny) = unsubnormal(x.abs());
+    let (_, p1, _, e) = cbrt_parts(a);
+    let y = correct(p1, e, [1. / 3., 2. / 9., 14. / 81.]);
+    let y = if tiny { y * hex(0x37800000) } else { y };
+    if a == f32::INFINITY || a == 0. {
+        a
+    } else {
+        y
+    }
+}
+
+/// x^(-1/3), 1/cbrt(x) with the real cube root, ±inf at ±0. Within 1 ulp.
+#[inline(always)]
+pub fn pow_m1_3(x: f32) -> f32 {
+    let (a, tiny) = unsubnormal(x.abs());
+    let (r, _, _, e) = cbrt_parts(a);
+    let y = correct(Df32(r, 0.), e, [1. / 3., 2. / 9., 14. / 81.]);
+    let y = if tiny { y * hex(0x43800000) } else { y };
+    let y = if a == 0. {
+        f32::INFINITY
+    } else if a == f32::INFINITY {
+        0.
+    } else {
+        y
+    };
+    y.copysign(x)
+}
+
+/// x^(4/3), x cbrt(x) with the real cube root, so even for x < 0. Within
+/// 1 ulp.
+#[inline(always)]
+pub fn pow_4_3(x: f32) -> f32 {
+    // not just subnormals, the tail of the product is below the result's
+    // precision once that's much under 2^-100
+    let a = x.abs();
+    let tiny = a < hex(0x27800000);
+    let a = if tiny { a * hex(0x63800000) } else { a };
+    let (_, _, p2, e) = cbrt_parts(a);
+    let p = p2 * a;
+    let y = correct(p, e, [2. / 3., 5. / 9., 40. / 81.]);
+    let y = if tiny { y * hex(0x0f800000) } else { y };
+    // the tail is NaN where the product overflows
+    if a == 0. || a == f32::INFINITY || p.0 == f32::INFINITY {
+        a * a
+    } else {
+        y
+    }
+}
+
+/// The n-th root x^(1/n) for any n, NaN for n = 0 and for x < 0 with n
+/// even, which keeps the sign of x otherwise. Within 1 ulp.
+#[inline(always)]
+pub fn rootn(x: f32, n: i32) -> f32 {
+    let odd = n & 1 != 0;
+    let s = if odd { 1f32.copysign(x) } else { 1. };
+    let a = x.abs();
+    // n only rounds past 2^24, where ln|x|/n is tiny anyway
+    let Df32(h, l) = ln_df(a) / n as f32;
+    let y = exp_mul(h, l, s);
+    // one Newton step on ln y = ln|x|/n takes out the exp's rounding, where
+    // the result isn't already 0 or inf
+    let Df32(d, dl) = Df32(h, l) - ln_df(y.abs());
+    let y = if y == 0. || y.abs() == f32::INFINITY {
+        y
+    } else {
+        fma(y, d + dl, y)
+    };
+    // 0 and inf both ways round
+    let edge = if n > 0 { a } else { 1. / a };
+    let y = if a == 0. || a == f32::INFINITY { edge * s } else { y };
+    if n == 0 || (x < 0. && !odd) || x.is_nan() {
+        f32::NAN
+    } else {
+        y
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::{random_positive, ulps};
+
+    #[test]
+    fn special_values() {
+        let (inf, nan) = (f32::INFINITY, f32::NAN);
+        assert_eq!(pow_1_4(16.), 2.);
+        assert_eq!(pow_1_4(0.0625), 0.5);
+        assert_eq!(pow_1_4(inf), inf);
+        assert_eq!(pow_1_4(-0.).to_bits(), 0);
+        assert!(pow_1_4(-1.).is_nan());
+        assert_eq!(pow_2_3(27.), 9.);
+        assert_eq!(pow_2_3(-8.), 4.);
+        assert_eq!(pow_2_3(-inf), inf);
+        assert_eq!(pow_2_3(-0.).to_bits(), 0);
+        assert_eq!(pow_m1_3(8.), 0.5);
+        assert_eq!(pow_m1_3(-0.125), -2.);
+        assert_eq!(pow_m1_3(0.), inf);
+        assert_eq!(pow_m1_3(-0.), -inf);
+        assert_eq!(pow_m1_3(-inf).to_bits(), (-0f32).to_bits());
+        assert_eq!(pow_4_3(27.), 81.);
+        assert_eq!(pow_4_3(-8.), 16.);
+        assert_eq!(pow_4_3(f32::MAX), inf);
+        assert_eq!(pow_4_3(-0.).to_bits(), 0);
+        for f in [pow_1_4, pow_2_3, pow_m1_3, pow_4_3] {
+            assert!(f(nan).is_nan());
+        }
+        assert_eq!(rootn(32., 5), 2.);
+        assert_eq!(rootn(-27., 3), -3.);
+        assert_eq!(rootn(0.25, -2), 2.);
+        assert_eq!(rootn(7., 1), 7.);
+        assert_eq!(rootn(-0., 3).to_bits(), (-0f32).to_bits());
+        assert_eq!(rootn(-0., 4).to_bits(), 0);
+        assert_eq!(rootn(-0., -3), -inf);
+        assert_eq!(rootn(0., -4), inf);
+        assert_eq!(rootn(-inf, 3), -inf);
+        assert_eq!(rootn(inf, -2), 0.);
+        assert!(rootn(-1., 2).is_nan());
+        assert!(rootn(2., 0).is_nan());
+        assert!(rootn(nan, 3).is_nan());
+    }
+
+    #[test]
+    fn perfect_powers() {
+        // the residual 1 - x r^q is exact and 0 at the root, so the last
+        // step can't move an exact result
+        for k in 1..64 {
+            let k = k as f32;
+            assert_eq!(pow_1_4(k * k * k * k), k);
+            assert_eq!(pow_2_3(k * k * k), k * k);
+            assert_eq!(pow_4_3(-(k * k * k)), k * k * k * k);
+            assert_eq!(rootn(k * k * k * k * k, 5), k);
+        }
+        for e in (-147..=127).step_by(3) {
+            assert_eq!(pow_m1_3(2f64.powi(e) as f32), 2f64.powi(-e / 3) as f32, "{e}");
+        }
+    }
+
+    #[test]
+    fn fixed_powers() {
+        let mut state = 0x2545f491;
+        for _ in 0..200_000 {
+            let x = random_positive(&mut state);
+            let xd = x as f64;
+            let expected = [xd.powf(0.25), xd.powf(2. / 3.), 1. / xd.cbrt(), xd * xd.cbrt()].map(|y| y as f32);
+            let got = [pow_1_4(x), pow_2_3(x), pow_m1_3(x), pow_4_3(x)];
+            for (g, e) in got.into_iter().zip(expected) {
+                assert!(ulps(g, e) <= 1, "{x}: {g} {e}");
+            }
+        }
+    }
+
+    #[test]
+    fn any_n() {
+        let mut state = 0x9e3779b9;
+        // n past 2^24 rounds when converted, ln|x|/n is far below an ulp of
+        // the result by then
+        for n in [1, 2, 3, 4, 5, 7, 12, 100, 1 << 20, 1 << 30, -1, -2, -3, -9, -1000] {
+            for _ in 0..10000 {
+                let x = random_positive(&mut state);
+                let y = rootn(x, n);
+                let expected = (x as f64).powf(1. / n as f64) as f32;
+                assert!(ulps(y, expected) <= 1, "{x} {n}: {y} {expected}");
+                if n & 1 != 0 {
+                    assert_eq!(rootn(-x, n), -y);
+                }
+            }
+        }
+    }
+}
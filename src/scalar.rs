@@ -0,0 +1,309 @@
+//! The scalar interface the kernels are written against.
+//!
+//! `f32` is the only type used for actual math, the others (`trace`, `bound`)
+//! run the exact same op sequence to record or analyze it. Everything is
+//! `#[inline(always)]` so the f32 instantiation compiles to the same code
+//! as writing the kernels on f32 directly.
+//!
+//! Without the `std` feature `f32::mul_add`, `floor` and `round` don't
+//! exist, the f32 impl falls back to the `soft` versions below. The
+//! `deterministic` feature uses them too, except for fma where the target
+//! has it in hardware, so the results don't depend on the platform's libm.
+
+use core::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Shl, Shr, Sub};
+
+const SIGN_MASK: u32 = 0x80000000;
+
+/// The integer view of a [`Float`], only the ops the kernels use.
+/// Constants are plain `u32` on the right hand side.
+pub trait Bits:
+    Copy
+    + Add<u32, Output = Self>
+    + Sub<u32, Output = Self>
+    + Mul<u32, Output = Self>
+    + Div<u32, Output = Self>
+    + BitAnd<u32, Output = Self>
+    + BitAnd<Self, Output = Self>
+    + BitOr<u32, Output = Self>
+    + BitXor<Self, Output = Self>
+    + Shl<u32, Output = Self>
+    + Shr<u32, Output = Self>
+{
+    fn lit(v: u32) -> Self;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+}
+
+pub trait Float:
+    Copy
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+{
+    type Bits: Bits;
+
+    fn lit(v: f32) -> Self;
+    fn to_bits(self) -> Self::Bits;
+    fn from_bits(b: Self::Bits) -> Self;
+    fn mul_add(self, b: Self, c: Self) -> Self;
+    fn floor(self) -> Self;
+    fn round(self) -> Self;
+    fn abs(self) -> Self;
+
+    /// Error-free addition (Knuth two-sum).
+    #[inline(always)]
+    fn two_sum(self, b: Self) -> (Self, Self) {
+        let s = self + b;
+        let v = s - self;
+        (s, (self - (s - v)) + (b - v))
+    }
+
+    /// Fast path when |self| >= |b| is guaranteed.
+    #[inline(always)]
+    fn quick_two_sum(self, b: Self) -> (Self, Self) {
+        let s = self + b;
+        (s, b - (s - self))
+    }
+
+    /// `self` with its sign flipped when `y` is negative.
+    #[inline(always)]
+    fn mulsign(self, y: Self) -> Self {
+        Self::from_bits(self.to_bits() ^ (y.to_bits() & SIGN_MASK))
+    }
+
+    /// Error-free product.
+    #[inline(always)]
+    fn two_prod(self, b: Self) -> (Self, Self) {
+        let p = self * b;
+        (p, self.mul_add(b, -p))
+    }
+
+    /// `self * b + c` and what it rounded off, error-free when `c - head`
+    /// and the remainder are representable, as in a range reduction.
+    #[inline(always)]
+    fn two_mul_add(self, b: Self, c: Self) -> (Self, Self) {
+        let y = self * b + c;
+        (y, self.mul_add(b, c - y))
+    }
+}
+
+impl Bits for u32 {
+    #[inline(always)]
+    fn lit(v: u32) -> Self {
+        v
+    }
+    #[inline(always)]
+    fn wrapping_add(self, rhs: Self) -> Self {
+        u32::wrapping_add(self, rhs)
+    }
+    #[inline(always)]
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        u32::wrapping_sub(self, rhs)
+    }
+}
+
+impl Float for f32 {
+    type Bits = u32;
+
+    #[inline(always)]
+    fn lit(v: f32) -> Self {
+        v
+    }
+    #[inline(always)]
+    fn to_bits(self) -> u32 {
+        f32::to_bits(self)
+    }
+    #[inline(always)]
+    fn from_bits(b: u32) -> Self {
+        f32::from_bits(b)
+    }
+    #[cfg(all(feature = "std", any(not(feature = "deterministic"), target_feature = "fma", target_arch = "aarch64")))]
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        f32::mul_add(self, b, c)
+    }
+    #[cfg(all(feature = "std", not(feature = "deterministic")))]
+    #[inline(always)]
+    fn floor(self) -> Self {
+        f32::floor(self)
+    }
+    #[cfg(all(feature = "std", not(feature = "deterministic")))]
+    #[inline(always)]
+    fn round(self) -> Self {
+        f32::round(self)
+    }
+    #[cfg(not(all(feature = "std", any(not(feature = "deterministic"), target_feature = "fma", target_arch = "aarch64"))))]
+    #[inline(always)]
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        soft::mul_add(self, b, c)
+    }
+    #[cfg(not(all(feature = "std", not(feature = "deterministic"))))]
+    #[inline(always)]
+    fn floor(self) -> Self {
+        soft::floor(self)
+    }
+    #[cfg(not(all(feature = "std", not(feature = "deterministic"))))]
+    #[inline(always)]
+    fn round(self) -> Self {
+        soft::round(self)
+    }
+    #[inline(always)]
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+}
+
+/// `f32::sqrt`, for the f32-only functions outside `kernels`. Correctly
+/// rounded either way, so `deterministic` keeps the std one.
+#[cfg(feature = "std")]
+#[inline(always)]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    f32::sqrt(x)
+}
+#[cfg(not(feature = "std"))]
+#[inline(always)]
+pub(crate) fn sqrt(x: f32) -> f32 {
+    soft::sqrt(x)
+}
+
+/// [`Float::mul_add`] on f32, so it follows the same features.
+#[inline(always)]
+pub(crate) fn fma(a: f32, b: f32, c: f32) -> f32 {
+    Float::mul_add(a, b, c)
+}
+
+/// An f32 constant from its bits, as the fitted coefficients are written.
+#[inline(always)]
+pub(crate) fn hex(bits: u32) -> f32 {
+    f32::from_bits(bits)
+}
+
+/// Core-only `mul_add`, `floor`, `round` and `sqrt`, bit-exact with the std
+/// ones. Also const, `kernels_const` is built on them.
+pub(crate) mod soft {
+    /// Single rounding fma through f64. The product of two f32s is exact in
+    /// f64, the sum is rounded to odd so the final f64 -> f32 rounding can't
+    /// round a second time in the wrong direction.
+    #[inline(always)]
+    pub const fn mul_add(a: f32, b: f32, c: f32) -> f32 {
+        let (x, y) = (a as f64 * b as f64, c as f64);
+        let s = x + y;
+        let v = s - x;
+        let e = (x - (s - v)) + (y - v);
+        let bits = s.to_bits();
+        if e != 0. && s.is_finite() && bits & 1 == 0 {
+            // the next f64 away from s in the direction of the error
+            let bits = if (e > 0.) == (s > 0.) { bits + 1 } else { bits - 1 };
+            return f64::from_bits(bits) as f32;
+        }
+        s as f32
+    }
+
+    // everything at or above 2^23 is already an integer
+    #[inline(always)]
+    const fn trunc(x: f32) -> f32 {
+        if x.abs() < 8388608. {
+            (x as i32 as f32).copysign(x)
+        } else {
+            x
+        }
+    }
+
+    #[inline(always)]
+    pub const fn floor(x: f32) -> f32 {
+        let t = trunc(x);
+        if t > x {
+            t - 1.
+        } else {
+            t
+        }
+    }
+
+    /// Newton steps in f64 from a bit-trick guess, then the neighbours are
+    /// checked against the exact squares of the midpoints (25 bits, so the
+    /// squares fit f64 exactly).
+    #[cfg_attr(feature = "std", allow(dead_code))]
+    #[inline(always)]
+    pub const fn sqrt(x: f32) -> f32 {
+        // 0, negatives, inf and NaN
+        if x.is_nan() || x <= 0. || x == f32::INFINITY {
+            return if x < 0. { f32::NAN } else { x };
+        }
+        let d = x as f64;
+        let y = f64::from_bits(0x1ff7a3bea91d9b1b + (d.to_bits() >> 1));
+        let y = 0.5 * (y + d / y);
+        let y = 0.5 * (y + d / y);
+        let y = 0.5 * (y + d / y);
+        let y = 0.5 * (y + d / y);
+        let r = y as f32;
+        let (down, up) = (f32::from_bits(r.to_bits() - 1), f32::from_bits(r.to_bits() + 1));
+        let lo = (down as f64 + r as f64) * 0.5;
+        let hi = (r as f64 + up as f64) * 0.5;
+        if lo * lo > d {
+            down
+        } else if hi * hi < d {
+            up
+        } else {
+            r
+        }
+    }
+
+    /// Half away from zero, like `f32::round`.
+    #[inline(always)]
+    pub const fn round(x: f32) -> f32 {
+        let t = trunc(x);
+        // exact, x and t share the exponent or t is 0
+        if (x - t).abs() >= 0.5 {
+            t + 1f32.copysign(x)
+        } else {
+            t
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::soft;
+
+    #[test]
+    fn soft_matches_std() {
+        let mut state = 0x9e3779b97f4a7c15_u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            // mostly moderate exponents so the fma terms actually interact
+            let bits = state as u32;
+            match state >> 62 {
+                0 => f32::from_bits(bits),
+                _ => f32::from_bits((bits & 0x80ffffff) | (0x3c000000 + (bits >> 24 & 0xf) * 0x00800000)),
+            }
+        };
+        let same = |a: f32, b: f32| a.to_bits() == b.to_bits() || (a.is_nan() && b.is_nan());
+        for _ in 0..2_000_000 {
+            let (a, b, c) = (next(), next(), next());
+            assert!(same(soft::mul_add(a, b, c), a.mul_add(b, c)), "fma({a:e}, {b:e}, {c:e})");
+            // the product's own low bits, where double rounding would show up
+            let p = -(a * b);
+            assert!(same(soft::mul_add(a, b, p), a.mul_add(b, p)), "fma({a:e}, {b:e}, {p:e})");
+            assert!(same(soft::floor(a), a.floor()), "floor({a:e})");
+            assert!(same(soft::round(a), a.round()), "round({a:e})");
+            assert!(same(soft::sqrt(a), a.sqrt()), "sqrt({a:e})");
+        }
+        for bits in (0..=u32::MAX).step_by(257) {
+            let x = f32::from_bits(bits);
+            assert!(same(soft::sqrt(x), x.sqrt()), "sqrt({x:e})");
+        }
+        for x in [0.5, -0.5, 1.5, -2.5, 0.49999997, -0.0, 8388607.5, -8388608.5, f32::MIN_POSITIVE] {
+            assert!(same(soft::round(x), x.round()), "round({x})");
+            assert!(same(soft::floor(x), x.floor()), "floor({x})");
+        }
+        // halfway cases for the f32 rounding with a tiny nonzero tail
+        let (a, b) = (1. + f32::EPSILON, 1. + f32::EPSILON);
+        for c in [f32::EPSILON * f32::EPSILON / 2., -1e-30, 1e-30, f32::from_bits(1)] {
+            assert!(same(soft::mul_add(a, b, c), a.mul_add(b, c)));
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use crate::doublefloat::{two_sum, Df32};
+
+// independent accumulators so the two-sum chains can run in parallel
+const LANES: usize = 8;
+// the lane corrections are plain f32 sums, flush them into the result
+// before they've soaked up enough rounding error to matter
+const BLOCK: usize = 256;
+
+#[inline(always)]
+fn fold_lanes(acc: Df32, s: &mut [f32; LANES], c: &mut [f32; LANES]) -> Df32 {
+    let mut acc = acc;
+    for i in 0..LANES {
+        acc = acc + Df32::from_add(s[i], c[i]);
+    }
+    *s = [0.; LANES];
+    *c = [0.; LANES];
+    acc
+}
+
+/// Sum of `xs` to roughly twice f32 precision (Ogita-Rump-Oishi Sum2).
+/// Only as accurate as a plain sum in twice the working precision: the
+/// error is bounded by about (n 2^-24)² Σ|x|, so relative to the result it
+/// grows with the condition number Σ|x| / |Σx|.
+pub fn sum_compensated(xs: &[f32]) -> Df32 {
+    let mut acc = Df32::from_f32(0.);
+    let mut s = [0f32; LANES];
+    let mut c = [0f32; LANES];
+    let mut blocks = xs.chunks_exact(LANES * BLOCK);
+    for block in &mut blocks {
+        for chunk in block.chunks_exact(LANES) {
+            for i in 0..LANES {
+                let (hi, e) = two_sum(s[i], chunk[i]);
+                s[i] = hi;
+                c[i] += e;
+            }
+        }
+        acc = fold_lanes(acc, &mut s, &mut c);
+    }
+    for &x in blocks.remainder() {
+        acc = acc + x;
+    }
+    acc
+}
+
+/// Dot product of `xs` and `ys` to roughly twice f32 precision
+/// (Ogita-Rump-Oishi Dot2), products are split with `Df32::from_mul`.
+///
+/// Panics if the slices have different lengths.
+pub fn dot_compensated(xs: &[f32], ys: &[f32]) -> Df32 {
+    assert_eq!(xs.len(), ys.len(), "dot_compensated: length mismatch");
+    let mut acc = Df32::from_f32(0.);
+    let mut s = [0f32; LANES];
+    let mut c = [0f32; LANES];
+    let mut xblocks = xs.chunks_exact(LANES * BLOCK);
+    let mut yblocks = ys.chunks_exact(LANES * BLOCK);
+    for (xb, yb) in (&mut xblocks).zip(&mut yblocks) {
+        for (xc, yc) in xb.chunks_exact(LANES).zip(yb.chunks_exact(LANES)) {
+            for i in 0..LANES {
+                let p = Df32::from_mul(xc[i], yc[i]);
+                let (hi, e) = two_sum(s[i], p.0);
+                s[i] = hi;
+                c[i] += e + p.1;
+            }
+        }
+        acc = fold_lanes(acc, &mut s, &mut c);
+    }
+    for (&x, &y) in xblocks.remainder().iter().zip(yblocks.remainder()) {
+        acc = acc + Df32::from_mul(x, y);
+    }
+    acc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{RngExt, SeedableRng};
+
+    // every test value is a multiple of 2^-40 below 2^40, so i128 fixed
+    // point gives the exact sum
+    const FIX: f64 = (1u64 << 40) as f64;
+
+    fn exact_sum(xs: impl Iterator<Item = f64>) -> f64 {
+        let fixed: i128 = xs.map(|x| (x * FIX) as i128).sum();
+        fixed as f64 / FIX
+    }
+
+    // big values of alternating sign with small ones in between, so the
+    // running sum keeps dropping the small parts in plain f32
+    fn ill_conditioned(n: usize, seed: u64) -> Vec<f32> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..n)
+            .map(|i| match i % 3 {
+                0 => rng.random_range(1e6_f32..1e7).round(),
+                1 => rng.random_range(-1.0_f32..1.0) / (1 << rng.random_range(0..16)) as f32,
+                _ => -rng.random_range(1e6_f32..1e7).round(),
+            })
+            .collect()
+    }
+
+    fn df_f64(d: Df32) -> f64 {
+        d.0 as f64 + d.1 as f64
+    }
+
+    #[test]
+    fn sum_matches_exact() {
+        for n in [0, 1, 7, 100, 2048, 1_000_001] {
+            let xs = ill_conditioned(n, n as u64);
+            let exact = exact_sum(xs.iter().map(|&x| x as f64));
+            let abs: f64 = xs.iter().map(|&x| x.abs() as f64).sum();
+            let got = df_f64(sum_compensated(&xs));
+            let naive: f32 = xs.iter().sum();
+            // f64 recursive summation could be off by n * 2^-53 * sum|x|
+            let f64_bound = n as f64 * f64::EPSILON / 2. * abs;
+            assert!(
+                (got - exact).abs() <= f64_bound.max(exact.abs() * 1e-14),
+                "n={n} got {got} exact {exact} naive {naive}"
+            );
+        }
+    }
+
+    #[test]
+    fn sum_cancellation() {
+        let mut xs = vec![1e8_f32, 1.0, -1e8];
+        xs.extend(std::iter::repeat_n([1e8_f32, 1.0, -1e8], 5000).flatten());
+        assert_eq!(sum_compensated(&xs).to_f32(), 5001.0);
+        assert_eq!(xs.iter().copied().sum::<Df32>().to_f32(), 5001.0);
+    }
+
+    #[test]
+    fn dot_matches_exact() {
+        let mut rng = StdRng::seed_from_u64(27);
+        for n in [0, 3, 64, 5000, 100_000] {
+            let quantize = |x: f32| (x as f64 * 1024.).round() as f32 / 1024.;
+            let xs: Vec<f32> = (0..n)
+                .map(|_| quantize(rng.random_range(-1000.0..1000.0)))
+                .collect();
+            let ys: Vec<f32> = (0..n)
+                .map(|_| quantize(rng.random_range(-1000.0..1000.0)))
+                .collect();
+            let products = xs.iter().zip(&ys).map(|(&x, &y)| x as f64 * y as f64);
+            let exact = exact_sum(products);
+            let abs: f64 = xs.iter().zip(&ys).map(|(&x, &y)| (x * y).abs() as f64).sum();
+            let got = df_f64(dot_compensated(&xs, &ys));
+            let f64_bound = n as f64 * f64::EPSILON * abs;
+            assert!(
+                (got - exact).abs() <= f64_bound.max(exact.abs() * 1e-14),
+                "n={n} got {got} exact {exact}"
+            );
+        }
+    }
+
+    #[test]
+    fn product() {
+        let xs = [1.5_f32, 3.0, 1.0 / 3.0, 7.0];
+        let p: Df32 = xs.iter().copied().product();
+        assert!((df_f64(p) - 1.5 * 3.0 * (1.0_f32 / 3.0) as f64 * 7.0).abs() < 1e-13);
+        let p: Df32 = xs.iter().map(|&x| Df32::from_f32(x)).product();
+        assert!((df_f64(p) - 1.5 * 3.0 * (1.0_f32 / 3.0) as f64 * 7.0).abs() < 1e-13);
+    }
+}
@@ -0,0 +1,34 @@
+//! Helpers shared by the unit tests.
+
+// xorshift over bit patterns, every binade equally likely
+fn next_bits(state: &mut u32) -> u32 {
+    *state ^= *state << 13;
+    *state ^= *state >> 17;
+    *state ^= *state << 5;
+    *state
+}
+
+/// A random finite f32 of either sign.
+pub(crate) fn random_finite(state: &mut u32) -> f32 {
+    loop {
+        let x = f32::from_bits(next_bits(state));
+        if x.is_finite() {
+            return x;
+        }
+    }
+}
+
+/// A random finite f32 above zero.
+pub(crate) fn random_positive(state: &mut u32) -> f32 {
+    loop {
+        let x = f32::from_bits(next_bits(state) >> 1);
+        if x.is_finite() && x > 0. {
+            return x;
+        }
+    }
+}
+
+/// Distance between two f32s of the same sign.
+pub(crate) fn ulps(a: f32, b: f32) -> u32 {
+    a.to_bits().abs_diff(b.to_bits())
+}
@@ -0,0 +1,587 @@
+//! Record a kernel's op graph by running it over [`Traced`], then bound
+//! its latency and throughput on a given microarchitecture.
+//!
+//! ```
+//! use jodiemath_rs::{kernels, trace};
+//! let graph = trace::trace(1.5, kernels::cbrt_accurate);
+//! let a = graph.analyze(&trace::TIGER_LAKE);
+//! println!("{} cycles latency, {:.2} cycles rthroughput", a.latency, a.throughput);
+//! ```
+//!
+//! The model is the usual static one: every op takes its table latency once
+//! its inputs are ready, constants are free (hoisted out of loops), and the
+//! throughput bound is the busiest set of ports or the issue width, whichever
+//! is worse. Dead ops are dropped like the compiler would.
+
+use crate::kernels;
+use crate::scalar::{Bits, Float};
+use std::cell::RefCell;
+use std::ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Neg, Shl, Shr, Sub};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Op {
+    Input,
+    Const,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Fma,
+    Neg,
+    Abs,
+    Floor,
+    Round,
+    ToBits,
+    FromBits,
+    IAdd,
+    ISub,
+    IMul,
+    // only ever by a constant, which compiles to a multiply and shifts
+    IDiv,
+    And,
+    Or,
+    Xor,
+    Shl,
+    Shr,
+}
+
+impl Op {
+    pub fn name(self) -> &'static str {
+        match self {
+            Op::Input => "input",
+            Op::Const => "const",
+            Op::Add => "add",
+            Op::Sub => "sub",
+            Op::Mul => "mul",
+            Op::Div => "div",
+            Op::Fma => "fma",
+            Op::Neg => "neg",
+            Op::Abs => "abs",
+            Op::Floor => "floor",
+            Op::Round => "round",
+            Op::ToBits => "to_bits",
+            Op::FromBits => "from_bits",
+            Op::IAdd => "iadd",
+            Op::ISub => "isub",
+            Op::IMul => "imul",
+            Op::IDiv => "idiv",
+            Op::And => "and",
+            Op::Or => "or",
+            Op::Xor => "xor",
+            Op::Shl => "shl",
+            Op::Shr => "shr",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ty {
+    F32,
+    U32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Node {
+    pub op: Op,
+    pub ty: Ty,
+    pub args: Vec<usize>,
+    /// The raw bits computed for the traced input.
+    pub value: u32,
+}
+
+thread_local! {
+    static NODES: RefCell<Vec<Node>> = const { RefCell::new(Vec::new()) };
+}
+
+fn push(op: Op, ty: Ty, args: &[usize], value: u32) -> usize {
+    NODES.with_borrow_mut(|nodes| {
+        if op == Op::Const {
+            if let Some(i) = nodes.iter().position(|n| n.op == Op::Const && n.ty == ty && n.value == value) {
+                return i;
+            }
+        }
+        nodes.push(Node { op, ty, args: args.to_vec(), value });
+        nodes.len() - 1
+    })
+}
+
+/// An f32 that records every op done on it.
+#[derive(Clone, Copy, Debug)]
+pub struct Traced {
+    id: usize,
+    value: f32,
+}
+
+/// The integer view of a [`Traced`].
+#[derive(Clone, Copy, Debug)]
+pub struct TracedBits {
+    id: usize,
+    value: u32,
+}
+
+impl Traced {
+    fn new(op: Op, args: &[usize], value: f32) -> Self {
+        Self { id: push(op, Ty::F32, args, value.to_bits()), value }
+    }
+    pub fn value(self) -> f32 {
+        self.value
+    }
+}
+
+impl TracedBits {
+    fn new(op: Op, args: &[usize], value: u32) -> Self {
+        Self { id: push(op, Ty::U32, args, value), value }
+    }
+    pub fn value(self) -> u32 {
+        self.value
+    }
+}
+
+macro_rules! float_op {
+    ($trait:ident, $method:ident, $op:ident) => {
+        impl $trait for Traced {
+            type Output = Self;
+            fn $method(self, rhs: Self) -> Self {
+                Traced::new(Op::$op, &[self.id, rhs.id], $trait::$method(self.value, rhs.value))
+            }
+        }
+    };
+}
+float_op!(Add, add, Add);
+float_op!(Sub, sub, Sub);
+float_op!(Mul, mul, Mul);
+float_op!(Div, div, Div);
+
+impl Neg for Traced {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Traced::new(Op::Neg, &[self.id], -self.value)
+    }
+}
+
+macro_rules! bits_op {
+    ($trait:ident, $method:ident, $op:ident, $eval:expr) => {
+        impl $trait for TracedBits {
+            type Output = Self;
+            fn $method(self, rhs: Self) -> Self {
+                let eval: fn(u32, u32) -> u32 = $eval;
+                TracedBits::new(Op::$op, &[self.id, rhs.id], eval(self.value, rhs.value))
+            }
+        }
+        impl $trait<u32> for TracedBits {
+            type Output = Self;
+            fn $method(self, rhs: u32) -> Self {
+                $trait::$method(self, TracedBits::lit(rhs))
+            }
+        }
+    };
+}
+bits_op!(Add, add, IAdd, u32::wrapping_add);
+bits_op!(Sub, sub, ISub, u32::wrapping_sub);
+bits_op!(Mul, mul, IMul, u32::wrapping_mul);
+bits_op!(Div, div, IDiv, |a, b| a / b);
+bits_op!(BitAnd, bitand, And, |a, b| a & b);
+bits_op!(BitOr, bitor, Or, |a, b| a | b);
+bits_op!(BitXor, bitxor, Xor, |a, b| a ^ b);
+bits_op!(Shl, shl, Shl, |a, b| a << b);
+bits_op!(Shr, shr, Shr, |a, b| a >> b);
+
+impl Bits for TracedBits {
+    fn lit(v: u32) -> Self {
+        TracedBits::new(Op::Const, &[], v)
+    }
+    fn wrapping_add(self, rhs: Self) -> Self {
+        self + rhs
+    }
+    fn wrapping_sub(self, rhs: Self) -> Self {
+        self - rhs
+    }
+}
+
+impl Float for Traced {
+    type Bits = TracedBits;
+
+    fn lit(v: f32) -> Self {
+        Traced::new(Op::Const, &[], v)
+    }
+    fn to_bits(self) -> TracedBits {
+        TracedBits::new(Op::ToBits, &[self.id], self.value.to_bits())
+    }
+    fn from_bits(b: TracedBits) -> Self {
+        Traced::new(Op::FromBits, &[b.id], f32::from_bits(b.value))
+    }
+    fn mul_add(self, b: Self, c: Self) -> Self {
+        Traced::new(Op::Fma, &[self.id, b.id, c.id], self.value.mul_add(b.value, c.value))
+    }
+    fn floor(self) -> Self {
+        Traced::new(Op::Floor, &[self.id], self.value.floor())
+    }
+    fn round(self) -> Self {
+        Traced::new(Op::Round, &[self.id], self.value.round())
+    }
+    fn abs(self) -> Self {
+        Traced::new(Op::Abs, &[self.id], self.value.abs())
+    }
+}
+
+/// The ops a kernel did on one input, in program order.
+#[derive(Clone, Debug)]
+pub struct Graph {
+    pub nodes: Vec<Node>,
+    pub output: usize,
+}
+
+/// Run `f` on `x` and record its graph. Traces can't be nested.
+pub fn trace(x: f32, f: impl FnOnce(Traced) -> Traced) -> Graph {
+    NODES.with_borrow_mut(|nodes| nodes.clear());
+    let out = f(Traced::new(Op::Input, &[], x));
+    Graph {
+        nodes: NODES.with_borrow_mut(std::mem::take),
+        output: out.id,
+    }
+}
+
+/// A micro-op that can issue on any port in the `ports` mask and keeps
+/// it busy for `cycles`.
+#[derive(Clone, Copy, Debug)]
+pub struct Uop {
+    pub ports: u8,
+    pub cycles: u32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Cost {
+    pub latency: u32,
+    pub uops: &'static [Uop],
+}
+
+pub struct Uarch {
+    pub name: &'static str,
+    /// Names for the bits of [`Uop::ports`].
+    pub ports: &'static [&'static str],
+    /// Uops issued per cycle.
+    pub width: u32,
+    pub cost: fn(Op) -> Cost,
+}
+
+const fn uop(ports: u8) -> Uop {
+    Uop { ports, cycles: 1 }
+}
+
+const P0: u8 = 1;
+const P01: u8 = 3;
+const P5: u8 = 4;
+const P015: u8 = 7;
+const DIVIDER: u8 = 8;
+
+const NONE: &[Uop] = &[];
+const FP: &[Uop] = &[uop(P01)];
+const FP2: &[Uop] = &[uop(P01), uop(P01)];
+const DIV: &[Uop] = &[uop(P0), Uop { ports: DIVIDER, cycles: 5 }];
+const ALU: &[Uop] = &[uop(P015)];
+const SHIFT: &[Uop] = &[uop(P01)];
+// two vpmuludq, a shift and a shuffle/blend to merge the halves
+const DIV_CONST: &[Uop] = &[uop(P01), uop(P01), uop(P01), uop(P5), uop(P015)];
+
+// ymm forms, numbers from uops.info for Ice Lake/Tiger Lake
+fn tiger_lake(op: Op) -> Cost {
+    let (latency, uops) = match op {
+        Op::Input | Op::Const => (0, NONE),
+        // no bypass delay between the integer and float domains on the
+        // vector side
+        Op::ToBits | Op::FromBits => (0, NONE),
+        Op::Add | Op::Sub | Op::Mul | Op::Fma => (4, FP),
+        Op::Div => (11, DIV),
+        Op::Floor | Op::Round => (8, FP2),
+        Op::Neg | Op::Abs | Op::And | Op::Or | Op::Xor | Op::IAdd | Op::ISub => (1, ALU),
+        Op::Shl | Op::Shr => (1, SHIFT),
+        Op::IMul => (10, FP2),
+        Op::IDiv => (8, DIV_CONST),
+    };
+    Cost { latency, uops }
+}
+
+pub const TIGER_LAKE: Uarch = Uarch {
+    name: "Tiger Lake",
+    ports: &["p0", "p1", "p5", "div"],
+    width: 5,
+    cost: tiger_lake,
+};
+
+#[derive(Clone, Debug)]
+pub struct Analysis {
+    /// Cycles from the input to the output.
+    pub latency: u32,
+    /// Node ids along the longest chain, input first.
+    pub critical_path: Vec<usize>,
+    /// Live ops, not counting the input and constants.
+    pub ops: usize,
+    pub uops: u32,
+    /// Lower bound on cycles per call when calls are independent.
+    pub throughput: f64,
+    /// The ports that set `throughput`, or empty if it's the issue width.
+    pub bottleneck: Vec<&'static str>,
+}
+
+impl Graph {
+    pub fn value(&self) -> f32 {
+        f32::from_bits(self.nodes[self.output].value)
+    }
+
+    /// Which nodes the output depends on.
+    pub fn live(&self) -> Vec<bool> {
+        let mut live = vec![false; self.nodes.len()];
+        live[self.output] = true;
+        for i in (0..self.nodes.len()).rev() {
+            if live[i] {
+                for &a in &self.nodes[i].args {
+                    live[a] = true;
+                }
+            }
+        }
+        live
+    }
+
+    pub fn analyze(&self, uarch: &Uarch) -> Analysis {
+        let live = self.live();
+        let mut ready = vec![0; self.nodes.len()];
+        let mut pred = vec![None; self.nodes.len()];
+        let mut load = vec![0; 1 << uarch.ports.len()];
+        let (mut ops, mut uops) = (0, 0);
+        // nodes are recorded in program order, so args come first
+        for (i, node) in self.nodes.iter().enumerate().filter(|&(i, _)| live[i]) {
+            let cost = (uarch.cost)(node.op);
+            let slowest = node
+                .args
+                .iter()
+                .copied()
+                .filter(|&a| self.nodes[a].op != Op::Const)
+                .max_by_key(|&a| ready[a]);
+            ready[i] = cost.latency + slowest.map_or(0, |a| ready[a]);
+            pred[i] = slowest;
+            if !cost.uops.is_empty() {
+                ops += 1;
+            }
+            for u in cost.uops {
+                load[u.ports as usize] += u.cycles;
+                uops += 1;
+            }
+        }
+
+        let mut critical_path = vec![self.output];
+        while let Some(p) = pred[*critical_path.last().unwrap()] {
+            critical_path.push(p);
+        }
+        critical_path.reverse();
+
+        // a set of ports has to execute every uop that can't go anywhere
+        // else, the busiest set per port is the bound
+        let mut throughput = uops as f64 / uarch.width as f64;
+        let mut bottleneck = vec![];
+        for set in 1..load.len() {
+            let cycles: u32 = (1..load.len()).filter(|&m| m & !set == 0).map(|m| load[m]).sum();
+            let bound = cycles as f64 / set.count_ones() as f64;
+            if bound > throughput {
+                throughput = bound;
+                bottleneck = (0..uarch.ports.len())
+                    .filter(|p| set & (1 << p) != 0)
+                    .map(|p| uarch.ports[p])
+                    .collect();
+            }
+        }
+
+        Analysis {
+            latency: ready[self.output],
+            critical_path,
+            ops,
+            uops,
+            throughput,
+            bottleneck,
+        }
+    }
+}
+
+impl Op {
+    // the labels and categories dataflow-graph.html uses for the same ops
+    fn html(self) -> (&'static str, &'static str) {
+        match self {
+            Op::Input => ("x", "input"),
+            Op::Const => ("", "literal"),
+            Op::Add | Op::IAdd => ("+", "op"),
+            Op::Sub | Op::ISub => ("-", "op"),
+            Op::Mul | Op::IMul => ("*", "op"),
+            Op::Div | Op::IDiv => ("/", "op"),
+            Op::And => ("&", "op"),
+            Op::Or => ("|", "op"),
+            Op::Xor => ("^", "op"),
+            Op::Shl => ("<<", "op"),
+            Op::Shr => (">>", "op"),
+            Op::Neg => ("neg", "op"),
+            Op::Fma => ("fma", "fn"),
+            Op::Abs => ("abs", "fn"),
+            Op::Floor => (".floor()", "conv"),
+            Op::Round => (".round()", "conv"),
+            Op::ToBits => (".to_bits()", "conv"),
+            Op::FromBits => ("f32::from_bits", "conv"),
+        }
+    }
+}
+
+impl Graph {
+    /// One kernel's live graph as a JSON object `dataflow-graph.html` can
+    /// load: dense node ids, per-node latency from `uarch`, constants as
+    /// hex, and the critical path marked on its nodes and edges.
+    pub fn to_json(&self, name: &str, uarch: &Uarch) -> String {
+        let a = self.analyze(uarch);
+        let live = self.live();
+        let ids: Vec<usize> = live
+            .iter()
+            .scan(0, |next, &l| {
+                *next += l as usize;
+                Some(*next - 1)
+            })
+            .collect();
+        let on_path = |i: usize| a.critical_path.contains(&i);
+        let mut nodes = vec![];
+        let mut edges = vec![];
+        for (i, n) in self.nodes.iter().enumerate().filter(|&(i, _)| live[i]) {
+            let (label, category) = n.op.html();
+            let hex = format!("0x{:08x}", n.value);
+            let label = if n.op == Op::Const { hex.as_str() } else { label };
+            let ty = match n.ty {
+                Ty::F32 => "f32",
+                Ty::U32 => "u32",
+            };
+            nodes.push(format!(
+                "{{\"id\": {}, \"op\": \"{}\", \"label\": \"{}\", \"category\": \"{}\", \"type_hint\": \"{}\", \"latency\": {}, \"value\": \"{}\", \"critical\": {}}}",
+                ids[i],
+                n.op.name(),
+                label,
+                category,
+                ty,
+                (uarch.cost)(n.op).latency,
+                hex,
+                on_path(i)
+            ));
+            for (port, &arg) in n.args.iter().enumerate() {
+                let critical = on_path(i) && a.critical_path.windows(2).any(|w| w == [arg, i]);
+                edges.push(format!(
+                    "{{\"from\": {}, \"to\": {}, \"port\": {}, \"critical\": {}}}",
+                    ids[arg], ids[i], port, critical
+                ));
+            }
+        }
+        let path: Vec<String> = a.critical_path.iter().map(|&i| ids[i].to_string()).collect();
+        format!(
+            "{{\"name\": \"{}\", \"uarch\": \"{}\", \"latency\": {}, \"throughput\": {}, \"ops\": {}, \"uops\": {}, \"critical_path\": [{}],\n \"nodes\": [\n  {}\n ],\n \"edges\": [\n  {}\n ]}}",
+            name,
+            uarch.name,
+            a.latency,
+            a.throughput,
+            a.ops,
+            a.uops,
+            path.join(", "),
+            nodes.join(",\n  "),
+            edges.join(",\n  ")
+        )
+    }
+}
+
+/// Kernels traced at their sample inputs, as one JSON document for
+/// `dataflow-graph.html`.
+pub fn kernels_json<'a>(kernels: impl IntoIterator<Item = &'a Kernel>, uarch: &Uarch) -> String {
+    let graphs: Vec<String> = kernels
+        .into_iter()
+        .map(|k| trace(k.sample, k.traced).to_json(k.name, uarch))
+        .collect();
+    format!("{{\"kernels\": [\n{}\n]}}\n", graphs.join(",\n"))
+}
+
+pub struct Kernel {
+    pub name: &'static str,
+    pub f: fn(f32) -> f32,
+    pub traced: fn(Traced) -> Traced,
+    /// An input in the main domain, for tracing.
+    pub sample: f32,
+}
+
+pub const KERNELS: &[Kernel] = &[
+    Kernel { name: "cbrt", f: crate::cbrt, traced: kernels::cbrt, sample: 10. },
+    Kernel { name: "cbrt_accurate", f: crate::cbrt_accurate, traced: kernels::cbrt_accurate, sample: 10. },
+    Kernel { name: "cbrt_throughput", f: crate::cbrt_throughput, traced: kernels::cbrt_throughput, sample: 10. },
+    Kernel { name: "cbrt_fast", f: crate::cbrt_fast, traced: kernels::cbrt_fast, sample: 10. },
+    Kernel { name: "exp2", f: crate::exp2, traced: kernels::exp2, sample: 0.3 },
+    Kernel { name: "log_2", f: crate::log_2, traced: kernels::log_2, sample: 10. },
+    Kernel { name: "sin", f: crate::sin, traced: kernels::sin, sample: 1. },
+    Kernel { name: "cos", f: crate::cos, traced: kernels::cos, sample: 1. },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_match() {
+        for k in KERNELS {
+            for x in [k.sample, 0.1, 1., 3.7, 100., 12345.] {
+                let graph = trace(x, k.traced);
+                assert_eq!(graph.value().to_bits(), (k.f)(x).to_bits(), "{} at {x}", k.name);
+            }
+        }
+    }
+
+    #[test]
+    fn chain_latency() {
+        let graph = trace(1., |x| {
+            let y = x.mul_add(x, x) + x;
+            let _dead = y / x;
+            y * Traced::lit(3.)
+        });
+        let a = graph.analyze(&TIGER_LAKE);
+        assert_eq!(a.latency, 12);
+        assert_eq!(a.ops, 3);
+        assert_eq!(a.critical_path.len(), 4);
+        assert_eq!(graph.value(), 9.);
+    }
+
+    #[test]
+    fn port_bound() {
+        // 8 divides are bound by the divider, not p0 or the issue width
+        let graph = trace(1., |x| (0..8).fold(x, |acc, _| acc + x / x));
+        let a = graph.analyze(&TIGER_LAKE);
+        assert_eq!(a.throughput, 40.);
+        assert_eq!(a.bottleneck, ["div"]);
+
+        // fp ops share p0 and p1
+        let graph = trace(1., |x| (0..10).fold(x, |acc, _| acc * x));
+        let a = graph.analyze(&TIGER_LAKE);
+        assert_eq!(a.throughput, 5.);
+        assert_eq!(a.bottleneck, ["p0", "p1"]);
+    }
+
+    #[test]
+    fn constants_dedup() {
+        let graph = trace(1., |x| x * Traced::lit(2.) + Traced::lit(2.));
+        let consts = graph.nodes.iter().filter(|n| n.op == Op::Const).count();
+        assert_eq!(consts, 1);
+    }
+
+    #[test]
+    fn json_export() {
+        let graph = trace(10., crate::kernels::cbrt_fast);
+        let a = graph.analyze(&TIGER_LAKE);
+        let json = graph.to_json("cbrt_fast", &TIGER_LAKE);
+        let live = graph.live().iter().filter(|&&l| l).count();
+        assert_eq!(json.matches("\"category\"").count(), live);
+        // the nodes on the path and the edges between them
+        let critical = json.matches("\"critical\": true}").count();
+        assert_eq!(critical, 2 * a.critical_path.len() - 1);
+        assert!(json.contains("\"label\": \"0x68ff2381\", \"category\": \"literal\", \"type_hint\": \"u32\""));
+        assert_eq!(json.matches('{').count(), json.matches('}').count());
+
+        let doc = kernels_json(KERNELS, &TIGER_LAKE);
+        for k in KERNELS {
+            assert!(doc.contains(&format!("\"name\": \"{}\"", k.name)));
+        }
+    }
+}
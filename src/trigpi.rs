@@ -0,0 +1,118 @@
+//! sin, cos and tan of πx, and sin and cos of 2πx for phases in turns.
+//! Subtracting the nearest even integer from x is exact, unlike the radian
+//! versions' subtraction of a multiple of 2π, so the reduction loses
+//! nothing. What's left in [-1, 1] is folded into [-1/2, 1/2] with another
+//! exact subtraction, scaled by π as a `Df32` and rounded once before
+//! `sinf_poly`, cos going through sin(π(1/2 - |y|)). Exact zeros keep the
+//! IEEE 754 signs: sinpi(n) is ±0 with the sign of n, cospi(n + 1/2) is
+//! +0, and tanpi is their quotient.
+
+use crate::doublefloat::consts::PI;
+use crate::kernels::sinf_poly;
+use crate::scalar::Float;
+use crate::Df32;
+
+// sin(πa) for |a| <= 1/2 given as a head and tail
+#[inline(always)]
+fn sin_pi(a: Df32) -> f32 {
+    sinf_poly((a * PI).to_f32())
+}
+
+// sin(πy) and cos(πy) for y in [-1, 1], x the unreduced argument for the
+// sign of a zero
+#[inline(always)]
+fn sincos_reduced(y: f32, x: f32) -> (f32, f32) {
+    // sin(πy) = sin(π(±1 - y)), both exact
+    let a = if y.abs() > 0.5 { 1f32.copysign(y) - y } else { y };
+    // cos(πy) = sin(π(1/2 - |y|)), which only rounds when |y| < 1/4, so
+    // it's kept as a Df32
+    let b = Df32::from_add(0.5, -y.abs());
+    let s = sin_pi(Df32(a, 0.));
+    let s = if a == 0. { x * 0. } else { s };
+    (s, sin_pi(b))
+}
+
+// x = 2k + y, y in [-1, 1], exact
+#[inline(always)]
+fn reduce_halfturns(x: f32) -> f32 {
+    x - 2. * Float::round(x * 0.5)
+}
+
+// x = k + y/2, y in [-1, 1], exact
+#[inline(always)]
+fn reduce_turns(x: f32) -> f32 {
+    2. * (x - Float::round(x))
+}
+
+/// sin(πx), ±0 at the integers.
+#[inline(always)]
+pub fn sinpi(x: f32) -> f32 {
+    sincos_reduced(reduce_halfturns(x), x).0
+}
+
+/// cos(πx), +0 at the half-integers.
+#[inline(always)]
+pub fn cospi(x: f32) -> f32 {
+    sincos_reduced(reduce_halfturns(x), x).1
+}
+
+/// tan(πx), ±inf at the half-integers, the sign of sinpi there.
+#[inline(always)]
+pub fn tanpi(x: f32) -> f32 {
+    let (s, c) = sincos_reduced(reduce_halfturns(x), x);
+    s / c
+}
+
+/// sin(2πx) for x in turns.
+#[inline(always)]
+pub fn sin_turns(x: f32) -> f32 {
+    sincos_reduced(reduce_turns(x), x).0
+}
+
+/// cos(2πx) for x in turns.
+#[inline(always)]
+pub fn cos_turns(x: f32) -> f32 {
+    sincos_reduced(reduce_turns(x), x).1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_values() {
+        for n in [-5f32, -2., -1., 1., 2., 3., 1e6, 3e7, 1e30] {
+            assert_eq!(sinpi(n).to_bits(), (0f32).copysign(n).to_bits(), "{n}");
+            assert_eq!(cospi(n), if n % 2. == 0. { 1. } else { -1. }, "{n}");
+            assert_eq!(sin_turns(n).to_bits(), (0f32).copysign(n).to_bits(), "{n}");
+            assert_eq!(cos_turns(n), 1., "{n}");
+        }
+        for n in [-5f32, -2., -1., 0., 1., 2., 3., 1e6] {
+            let h = n + 0.5;
+            assert_eq!(cospi(h).to_bits(), 0, "{h}");
+            assert_eq!(sinpi(h), if n % 2. == 0. { 1. } else { -1. }, "{h}");
+            assert_eq!(tanpi(h), sinpi(h) * f32::INFINITY, "{h}");
+        }
+        assert_eq!(sinpi(-0.).to_bits(), (-0f32).to_bits());
+        assert_eq!(tanpi(-0.).to_bits(), (-0f32).to_bits());
+        assert_eq!(sinpi(0.5), 1.);
+        assert_eq!(tanpi(0.25), 1.);
+        assert_eq!(sin_turns(0.25), 1.);
+        assert_eq!(cos_turns(0.5), -1.);
+        assert_eq!(sinpi(1e-30), 1e-30 * core::f32::consts::PI);
+        for f in [sinpi, cospi, tanpi, sin_turns, cos_turns] {
+            assert!(f(f32::INFINITY).is_nan());
+            assert!(f(f32::NAN).is_nan());
+        }
+    }
+
+    #[test]
+    fn phases_beyond_radians() {
+        // x π isn't representable, sin of it is off by the rounding
+        // times 1e4, where the reduction here is exact
+        let x = 10000.1f32;
+        let expected = (core::f64::consts::PI * (x as f64 - 10000.)).sin();
+        assert!((sinpi(x) as f64 - expected).abs() < 1e-7);
+        assert!(((x * core::f32::consts::PI).sin() as f64 - expected).abs() > 1e-4);
+    }
+}
@@ -0,0 +1,710 @@
+//! Random descent over integer constants (magic numbers, coefficient bit
+//! patterns). Seeded and budgeted so searches are reproducible and can run
+//! unattended, evaluation is spread over threads.
+//!
+//! ```no_run
+//! use jodiemath_rs::{cbrt_constant, tune};
+//! let inputs = tune::uniform_inputs(1, 0.0..1.0, 10_000);
+//! let opts = tune::Options {
+//!     budget: tune::Budget::Iterations(100_000),
+//!     output: Some("cbrt_consts.txt".into()),
+//!     ..Default::default()
+//! };
+//! let best = tune::descend(cbrt_constant, |x| (x as f64).cbrt() as f32, &inputs, &[0x2a5063f7], &opts);
+//! println!("{:x?} {}", best.consts, best.error);
+//! ```
+
+use crate::scalar::fma;
+use crate::{kernels, rootn};
+use std::ops::Range;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// What `descend` minimizes over the inputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    MeanUlp,
+    MaxUlp,
+    /// mean of |f(x) / reference(x) - 1|
+    RelErr,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Budget {
+    Iterations(u64),
+    /// not reproducible, how far it gets depends on the machine
+    Time(Duration),
+}
+
+#[derive(Clone, Debug)]
+pub struct Options {
+    pub seed: u64,
+    pub budget: Budget,
+    pub objective: Objective,
+    /// 0 uses every available core
+    pub threads: usize,
+    /// rewritten with the best constants every time they improve
+    pub output: Option<PathBuf>,
+    pub verbose: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            budget: Budget::Iterations(10_000),
+            objective: Objective::MeanUlp,
+            threads: 0,
+            output: None,
+            verbose: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Tuned {
+    pub consts: Vec<u32>,
+    pub error: f64,
+    pub steps: u64,
+}
+
+/// SplitMix64. Hand rolled so a seed gives the same search on every
+/// platform and every version of the rand crate.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let z = (self.0 ^ (self.0 >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        let z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+    pub fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+    /// uniform in [0, 1)
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u32() >> 8) as f32 / (1 << 24) as f32
+    }
+}
+
+/// `n` inputs spread uniformly over `range`.
+pub fn uniform_inputs(seed: u64, range: Range<f32>, n: usize) -> Vec<f32> {
+    let mut rng = Rng::new(seed);
+    (0..n)
+        .map(|_| range.start + (range.end - range.start) * rng.next_f32())
+        .collect()
+}
+
+// fixed size so the f64 sums come out the same for any thread count
+const CHUNK: usize = 1024;
+// chunks a thread has to get before spawning it beats doing them here,
+// `descend` evaluates thousands of times
+const CHUNKS_PER_THREAD: usize = 16;
+
+/// Error of `f` with `consts` over `inputs`, lower is better.
+pub fn evaluate(
+    f: &(impl Fn(f32, &[u32]) -> f32 + Sync),
+    expected: &[f32],
+    inputs: &[f32],
+    consts: &[u32],
+    objective: Objective,
+    threads: usize,
+) -> f64 {
+    let chunk_err = |xs: &[f32], ys: &[f32]| -> f64 {
+        let errs = xs.iter().zip(ys).map(|(&x, &y)| {
+            let r = f(x, consts);
+            match objective {
+                Objective::MeanUlp | Objective::MaxUlp => y.to_bits().abs_diff(r.to_bits()) as f64,
+                Objective::RelErr => (r as f64 / y as f64 - 1.).abs(),
+            }
+        });
+        match objective {
+            Objective::MaxUlp => errs.fold(0., f64::max),
+            _ => errs.sum(),
+        }
+    };
+    if inputs.is_empty() {
+        return 0.;
+    }
+    let chunks: Vec<(&[f32], &[f32])> = inputs.chunks(CHUNK).zip(expected.chunks(CHUNK)).collect();
+    let mut per_chunk = vec![0.; chunks.len()];
+    let threads = threads.clamp(1, chunks.len().div_ceil(CHUNKS_PER_THREAD));
+    if threads == 1 {
+        for (o, &(xs, ys)) in per_chunk.iter_mut().zip(&chunks) {
+            *o = chunk_err(xs, ys);
+        }
+    } else {
+        let per_thread = chunks.len().div_ceil(threads);
+        std::thread::scope(|scope| {
+            for (out, chunks) in per_chunk.chunks_mut(per_thread).zip(chunks.chunks(per_thread)) {
+                let chunk_err = &chunk_err;
+                scope.spawn(move || {
+                    for (o, &(xs, ys)) in out.iter_mut().zip(chunks) {
+                        *o = chunk_err(xs, ys);
+                    }
+                });
+            }
+        });
+    }
+    match objective {
+        Objective::MaxUlp => per_chunk.into_iter().fold(0., f64::max),
+        _ => per_chunk.into_iter().sum::<f64>() / inputs.len().max(1) as f64,
+    }
+}
+
+fn write_output(opts: &Options, best: &Tuned) {
+    if let Some(path) = &opts.output {
+        let consts: Vec<String> = best.consts.iter().map(|c| format!("0x{c:08x}")).collect();
+        let text = format!(
+            "{}\n# {:?} {} after {} steps, seed {}\n",
+            consts.join(", "),
+            opts.objective,
+            best.error,
+            best.steps,
+            opts.seed
+        );
+        if let Err(e) = std::fs::write(path, text) {
+            eprintln!("couldn't write {}: {e}", path.display());
+        }
+    }
+}
+
+/// Random walk over `initial` minimizing the error of `f` against
+/// `reference` on `inputs`. Steps alternate between arbitrary jumps and
+/// small log-distributed nudges, a step that helped is tried again.
+pub fn descend(
+    f: impl Fn(f32, &[u32]) -> f32 + Sync,
+    reference: impl Fn(f32) -> f32,
+    inputs: &[f32],
+    initial: &[u32],
+    opts: &Options,
+) -> Tuned {
+    let threads = match opts.threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let expected: Vec<f32> = inputs.iter().map(|&x| reference(x)).collect();
+    let eval = |consts: &[u32]| evaluate(&f, &expected, inputs, consts, opts.objective, threads);
+    let mut rng = Rng::new(opts.seed);
+    let mut consts = initial.to_vec();
+    let mut err = eval(&consts);
+    let mut best = Tuned {
+        consts: consts.clone(),
+        error: err,
+        steps: 0,
+    };
+    if opts.verbose {
+        println!("optimizing! starting error: {err}");
+    }
+    let start = Instant::now();
+    let mut deltas = vec![0u32; consts.len()];
+    let mut tryagain = false;
+    let mut steps: u64 = 0;
+    loop {
+        match opts.budget {
+            Budget::Iterations(n) if steps >= n => break,
+            Budget::Time(t) if start.elapsed() >= t => break,
+            _ => {}
+        }
+        steps += 1;
+        if !tryagain {
+            for d in deltas.iter_mut() {
+                let n = rng.next_u32();
+                // the bits of a random float cast to int are spread over
+                // every magnitude, mostly small
+                *d = if steps < 10 || steps & 1 == 0 {
+                    n
+                } else {
+                    f32::from_bits(n) as i32 as u32
+                };
+            }
+        }
+        let new_consts: Vec<u32> = consts
+            .iter()
+            .zip(&deltas)
+            .map(|(&c, &d)| c.wrapping_add(d))
+            .collect();
+        if new_consts == consts {
+            tryagain = false;
+            continue;
+        }
+        let new_err = eval(&new_consts);
+        tryagain = new_err < err;
+        if new_err <= err {
+            consts = new_consts;
+            err = new_err;
+        }
+        if err < best.error {
+            best = Tuned {
+                consts: consts.clone(),
+                error: err,
+                steps,
+            };
+            if opts.verbose {
+                println!("new best consts {:x?} with error {err} step:{steps}", consts);
+            }
+            write_output(opts, &best);
+        }
+    }
+    best
+}
+
+// ── Magic constant search ───────────────────────────────────────────
+
+/// A bit-trick seed for `magic` to tune, with the refinement that follows
+/// it in the kernel.
+pub struct Seed {
+    pub name: &'static str,
+    /// the constants the kernel uses
+    pub consts: &'static [u32],
+    /// period of the seed error in binades
+    pub binades: u32,
+    /// how many refinement steps the kernel runs
+    pub steps: u32,
+    /// the seed from the given constants, then that many steps
+    pub f: fn(f32, &[u32], u32) -> f32,
+    pub reference: fn(f64) -> f64,
+}
+
+/// The cbrt and rootn seeds run the kernels' own seed and step functions.
+/// rsqrt, rcp and sqrt aren't kernels, their steps are plain Newton.
+pub const SEEDS: &[Seed] = &[
+    Seed {
+        name: "cbrt",
+        consts: &[0x2a509a07],
+        binades: 3,
+        steps: 1,
+        f: |x, c, steps| (0..steps).fold(kernels::cbrt_seed(x, c[0]), |s, _| kernels::cbrt_step(x, s)),
+        reference: f64::cbrt,
+    },
+    Seed {
+        name: "cbrt_accurate",
+        consts: &[0x2a4ddef1, 0x68ff2381],
+        binades: 3,
+        steps: 2,
+        f: |x, c, steps| {
+            let (s, r) = kernels::cbrt_accurate_seed(x, [c[0], c[1]]);
+            (0..steps).fold(s, |s, _| kernels::cbrt_accurate_step(x, s, r))
+        },
+        reference: f64::cbrt,
+    },
+    // x^(-1/3), the seed pow_2_3 and pow_4_3 also start from
+    Seed {
+        name: "pow_m1_3",
+        consts: &[0x54a21409],
+        binades: 3,
+        steps: 1,
+        f: |x, c, steps| (0..steps).fold(rootn::rcbrt_seed(x, c[0]), |r, _| rootn::rcbrt_step(x, r)),
+        reference: |x| 1. / x.cbrt(),
+    },
+    // x^(-1/4), the seed of pow_1_4
+    Seed {
+        name: "pow_m1_4",
+        consts: &[0x4f583c29],
+        binades: 4,
+        steps: 1,
+        f: |x, c, steps| (0..steps).fold(rootn::rqrt_seed(x, c[0]), |r, _| rootn::rqrt_step(x, r)),
+        reference: |x| 1. / x.sqrt().sqrt(),
+    },
+    Seed {
+        name: "rsqrt",
+        consts: &[0x5F33E79F],
+        binades: 2,
+        steps: 0,
+        f: |x, c, steps| {
+            let y = f32::from_bits(c[0].wrapping_sub(x.to_bits() >> 1));
+            (0..steps).fold(y, |y, _| y * fma(-0.5 * x, y * y, 1.5))
+        },
+        reference: |x| 1. / x.sqrt(),
+    },
+    Seed {
+        name: "rcp",
+        consts: &[0x7EEF370B],
+        binades: 1,
+        steps: 0,
+        f: |x, c, steps| {
+            let y = f32::from_bits(c[0].wrapping_sub(x.to_bits()));
+            (0..steps).fold(y, |y, _| fma(fma(-x, y, 1.), y, y))
+        },
+        reference: |x| 1. / x,
+    },
+    Seed {
+        name: "sqrt",
+        consts: &[0x1FBD22DF],
+        binades: 2,
+        steps: 0,
+        f: |x, c, steps| {
+            let y = f32::from_bits(c[0].wrapping_add(x.to_bits() >> 1));
+            (0..steps).fold(y, |y, _| 0.5 * (y + x / y))
+        },
+        reference: f64::sqrt,
+    },
+];
+
+/// Every `stride`th f32 in the `binades` binades starting at `start`.
+/// Seeds like `c + bits / 3` have errors that repeat every 3 binades (every
+/// 2 for `bits >> 1`, every one for `c - bits`), so one period with stride
+/// 1 covers every input there is.
+pub fn binade_inputs(start: f32, binades: u32, stride: usize) -> Vec<f32> {
+    let first = start.to_bits() & 0x7f800000;
+    (first..first + (binades << 23))
+        .step_by(stride.max(1))
+        .map(f32::from_bits)
+        .collect()
+}
+
+/// Max of |f(x) / reference(x) - 1| over `inputs`, in parallel.
+pub fn max_rel_error(
+    f: &(impl Fn(f32, &[u32]) -> f32 + Sync),
+    expected: &[f64],
+    inputs: &[f32],
+    consts: &[u32],
+    threads: usize,
+) -> f64 {
+    let threads = threads.max(1);
+    let per_thread = inputs.len().div_ceil(threads).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = inputs
+            .chunks(per_thread)
+            .zip(expected.chunks(per_thread))
+            .map(|(xs, ys)| {
+                scope.spawn(move || {
+                    xs.iter()
+                        .zip(ys)
+                        .map(|(&x, &y)| (f(x, consts) as f64 / y - 1.).abs())
+                        // NaN counts as the worst possible error
+                        .fold(0., |m: f64, e| if e.is_nan() { f64::INFINITY } else { m.max(e) })
+                })
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).fold(0., f64::max)
+    })
+}
+
+/// Minimize the max relative error of `f` over `inputs` one constant at a
+/// time: ternary search over `initial ± span`, then every constant within
+/// `window` of the result. The ternary search assumes the max error is
+/// unimodal in the constant, which nothing checks past the window, so the
+/// result is the best found rather than proven best, for that see
+/// `scan_magic`.
+/// Constants are swept in turn until none of them move.
+pub fn search_magic(
+    f: impl Fn(f32, &[u32]) -> f32 + Sync,
+    reference: impl Fn(f32) -> f64,
+    inputs: &[f32],
+    initial: &[u32],
+    span: u32,
+    window: u32,
+    threads: usize,
+) -> Tuned {
+    let threads = match threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    let expected: Vec<f64> = inputs.iter().map(|&x| reference(x)).collect();
+    let mut consts = initial.to_vec();
+    let mut steps = 0;
+    let mut eval = |consts: &[u32]| {
+        steps += 1;
+        max_rel_error(&f, &expected, inputs, consts, threads)
+    };
+    let mut err = eval(&consts);
+    for _sweep in 0..8 {
+        let before = consts.clone();
+        for i in 0..consts.len() {
+            let mut with = |c: u32| {
+                let mut cs = consts.clone();
+                cs[i] = c;
+                eval(&cs)
+            };
+            let (mut lo, mut hi) = (consts[i].saturating_sub(span), consts[i].saturating_add(span));
+            while hi - lo > 2 {
+                let m1 = lo + (hi - lo) / 3;
+                let m2 = hi - (hi - lo) / 3;
+                if with(m1) < with(m2) {
+                    hi = m2;
+                } else {
+                    lo = m1;
+                }
+            }
+            let mid = lo + (hi - lo) / 2;
+            let (mut best_c, mut best_e) = (consts[i], err);
+            for c in mid.saturating_sub(window)..=mid.saturating_add(window) {
+                let e = with(c);
+                if e < best_e {
+                    (best_c, best_e) = (c, e);
+                }
+            }
+            consts[i] = best_c;
+            err = best_e;
+        }
+        if consts == before {
+            break;
+        }
+    }
+    Tuned {
+        consts,
+        error: err,
+        steps,
+    }
+}
+
+/// Every combination of constants within `span` of `initial`, jointly,
+/// for the least max relative error over `inputs`, the lowest constants
+/// winning a tie. With one constant and a span of `u32::MAX` that's every
+/// u32 there is, so the result is the optimum over `inputs`. A candidate is
+/// dropped at the first input that puts it past the best so far, trying the
+/// inputs that dropped earlier ones first, so most cost an evaluation or
+/// two. Over many inputs the box is scanned on every 64th first so the best
+/// starts out close. `steps` counts the candidates.
+///
+/// Panics with more than four constants, or a span too wide to count.
+pub fn scan_magic(
+    f: impl Fn(f32, &[u32]) -> f32 + Sync,
+    reference: impl Fn(f32) -> f64,
+    inputs: &[f32],
+    initial: &[u32],
+    span: u32,
+    threads: usize,
+) -> Tuned {
+    let threads = match threads {
+        0 => std::thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    };
+    assert!(initial.len() <= 4, "scan_magic: at most four constants");
+    let expected: Vec<f64> = inputs.iter().map(|&x| reference(x)).collect();
+    let ranges: Vec<(u32, u32)> = initial.iter().map(|&c| (c.saturating_sub(span), c.saturating_add(span))).collect();
+    let total = ranges
+        .iter()
+        .try_fold(1u64, |n, &(lo, hi)| n.checked_mul((hi - lo) as u64 + 1))
+        .expect("scan_magic: too many candidates");
+    let (error, consts) = scan_box(&f, &expected, inputs, &ranges, total, initial, threads);
+    Tuned {
+        consts,
+        error,
+        steps: total,
+    }
+}
+
+// the least max error in the box of `ranges` and the constants with it
+fn scan_box<F: Fn(f32, &[u32]) -> f32 + Sync>(
+    f: &F,
+    expected: &[f64],
+    inputs: &[f32],
+    ranges: &[(u32, u32)],
+    total: u64,
+    initial: &[u32],
+    threads: usize,
+) -> (f64, Vec<u32>) {
+    // candidate k in order, the first constant varying slowest
+    let decode = |mut k: u64, cs: &mut [u32; 4]| {
+        for i in (0..ranges.len()).rev() {
+            let (lo, hi) = ranges[i];
+            let len = (hi - lo) as u64 + 1;
+            cs[i] = lo + (k % len) as u32;
+            k /= len;
+        }
+    };
+    let index = |cs: &[u32]| {
+        ranges
+            .iter()
+            .zip(cs)
+            .fold(0, |k, (&(lo, hi), &c)| k * ((hi - lo) as u64 + 1) + (c - lo) as u64)
+    };
+    let err = |cs: &[u32], j: usize| {
+        let e = (f(inputs[j], cs) as f64 / expected[j] - 1.).abs();
+        // NaN counts as the worst possible error
+        if e.is_nan() {
+            f64::INFINITY
+        } else {
+            e
+        }
+    };
+    // Scanning up from a bad start, every candidate on the way down to the
+    // best would be a new best that went through every input. The best over
+    // every 64th input is close enough that hardly anything beats it.
+    let start = if inputs.len() >= 1 << 16 {
+        let xs: Vec<f32> = inputs.iter().step_by(64).copied().collect();
+        let ys: Vec<f64> = expected.iter().step_by(64).copied().collect();
+        scan_box(f, &ys, &xs, ranges, total, initial, threads).1
+    } else {
+        initial.to_vec()
+    };
+    // error and candidate, shared so each thread prunes against the best
+    // any of them found
+    let best = std::sync::Mutex::new((max_rel_error(f, expected, inputs, &start, threads), index(&start)));
+    let next = std::sync::atomic::AtomicU64::new(0);
+    let chunk = (total / (threads as u64 * 256)).clamp(1, 1 << 16);
+    std::thread::scope(|scope| {
+        for _ in 0..threads {
+            scope.spawn(|| {
+                let mut witnesses: Vec<usize> = Vec::new();
+                let mut cs = [0u32; 4];
+                loop {
+                    let start = next.fetch_add(chunk, std::sync::atomic::Ordering::Relaxed);
+                    if start >= total {
+                        break;
+                    }
+                    let mut local = *best.lock().unwrap();
+                    for k in start..(start + chunk).min(total) {
+                        decode(k, &mut cs);
+                        let cs = &cs[..ranges.len()];
+                        // past the best, or level with an earlier candidate
+                        let beaten = |e: f64| e > local.0 || (e == local.0 && k > local.1);
+                        if witnesses.iter().any(|&j| beaten(err(cs, j))) {
+                            continue;
+                        }
+                        // a coarse pass first drops most of the ones close
+                        // to the best without going through every input
+                        let mut worst = 0f64;
+                        let dropped_at = [1 << 12, 1 << 8, 1 << 4, 1].into_iter().find_map(|stride| {
+                            (0..inputs.len()).step_by(stride).find(|&j| {
+                                worst = worst.max(err(cs, j));
+                                beaten(worst)
+                            })
+                        });
+                        if let Some(j) = dropped_at {
+                            witnesses.retain(|&w| w != j);
+                            witnesses.insert(0, j);
+                            witnesses.truncate(16);
+                            continue;
+                        }
+                        let mut shared = best.lock().unwrap();
+                        if (worst, k) < *shared {
+                            *shared = (worst, k);
+                        }
+                        local = *shared;
+                    }
+                }
+            });
+        }
+    });
+    let (error, k) = best.into_inner().unwrap();
+    let mut cs = [0u32; 4];
+    decode(k, &mut cs);
+    (error, cs[..ranges.len()].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed_only(x: f32, c: &[u32]) -> f32 {
+        f32::from_bits(c[0].wrapping_add(x.to_bits() / 3))
+    }
+
+    #[test]
+    fn reproducible() {
+        let inputs = uniform_inputs(7, 1.0..8.0, 2000);
+        let run = |threads| {
+            let opts = Options {
+                seed: 3,
+                budget: Budget::Iterations(3000),
+                objective: Objective::RelErr,
+                threads,
+                ..Default::default()
+            };
+            descend(seed_only, f32::cbrt, &inputs, &[0x2a000000], &opts)
+        };
+        let (a, b) = (run(1), run(4));
+        assert_eq!(a.consts, b.consts);
+        assert_eq!(a.error, b.error);
+        let start = evaluate(
+            &seed_only,
+            &inputs.iter().map(|x| x.cbrt()).collect::<Vec<_>>(),
+            &inputs,
+            &[0x2a000000],
+            Objective::RelErr,
+            1,
+        );
+        assert!(a.error < start / 2., "{} {start}", a.error);
+    }
+
+    #[test]
+    fn binades() {
+        let xs = binade_inputs(1.0, 1, 1);
+        assert_eq!(xs.len(), 1 << 23);
+        assert_eq!((xs[0], *xs.last().unwrap()), (1.0, 2.0 - f32::EPSILON));
+        let xs = binade_inputs(3.0, 3, 1 << 20);
+        assert_eq!(xs.len(), 24);
+        assert_eq!((xs[0], xs[1], *xs.last().unwrap()), (2.0, 2.25, 15.0));
+    }
+
+    #[test]
+    fn rsqrt_magic() {
+        // seed only, the best max error constant is known to be 0x5f37642f
+        let inputs = binade_inputs(1.0, 2, 97);
+        let f = |x: f32, c: &[u32]| f32::from_bits(c[0].wrapping_sub(x.to_bits() >> 1));
+        let best = search_magic(f, |x| 1. / (x as f64).sqrt(), &inputs, &[0x5f300000], 1 << 20, 4, 4);
+        assert!(best.consts[0].abs_diff(0x5f37642f) < 64, "{:x}", best.consts[0]);
+        assert!(best.error < 0.0344, "{}", best.error);
+    }
+
+    #[test]
+    fn scan_is_exhaustive() {
+        // brute force over the same span agrees, and the ternary search
+        // can't beat it
+        let inputs = binade_inputs(1.0, 2, 4099);
+        let f = |x: f32, c: &[u32]| f32::from_bits(c[0].wrapping_sub(x.to_bits() >> 1));
+        let reference = |x: f32| 1. / (x as f64).sqrt();
+        let expected: Vec<f64> = inputs.iter().map(|&x| reference(x)).collect();
+        let start = 0x5f376000;
+        let scanned = scan_magic(f, reference, &inputs, &[start], 1 << 12, 4);
+        let brute = (start - (1 << 12)..=start + (1 << 12))
+            .map(|c| (max_rel_error(&f, &expected, &inputs, &[c], 1), c))
+            .fold((f64::INFINITY, 0), |a, b| if b.0 < a.0 { b } else { a });
+        assert_eq!((scanned.error, scanned.consts[0]), brute);
+        let searched = search_magic(f, reference, &inputs, &[start], 1 << 12, 4, 4);
+        assert!(searched.error >= scanned.error);
+    }
+
+    #[test]
+    fn scan_is_joint() {
+        // cbrt_accurate's two constants over a small box, every pair by
+        // brute force agrees
+        let seed = SEEDS.iter().find(|s| s.name == "cbrt_accurate").unwrap();
+        let inputs = binade_inputs(1.0, 3, 1021);
+        let f = |x: f32, c: &[u32]| (seed.f)(x, c, seed.steps);
+        let reference = |x: f32| (seed.reference)(x as f64);
+        let expected: Vec<f64> = inputs.iter().map(|&x| reference(x)).collect();
+        let start = [0x2a4dde00, 0x68ff2400];
+        let scanned = scan_magic(f, reference, &inputs, &start, 6, 3);
+        let brute = (start[0] - 6..=start[0] + 6)
+            .flat_map(|a| (start[1] - 6..=start[1] + 6).map(move |b| [a, b]))
+            .map(|cs| (max_rel_error(&f, &expected, &inputs, &cs, 1), cs))
+            .fold((f64::INFINITY, [0; 2]), |a, b| if b.0 < a.0 { b } else { a });
+        assert_eq!((scanned.error, scanned.consts.as_slice()), (brute.0, brute.1.as_slice()));
+        assert_eq!(scanned.steps, 13 * 13);
+    }
+
+    #[test]
+    fn objectives() {
+        let inputs = [1., 8., 27.];
+        let expected = [1., 2., 3.];
+        let f = |x: f32, c: &[u32]| x.cbrt() + f32::from_bits(c[0]);
+        let one = 1_f32.to_bits();
+        assert_eq!(evaluate(&f, &expected, &inputs, &[0], Objective::MaxUlp, 2), 0.);
+        let mean = evaluate(&f, &expected, &inputs, &[one], Objective::MeanUlp, 2);
+        let max = evaluate(&f, &expected, &inputs, &[one], Objective::MaxUlp, 2);
+        let rel = evaluate(&f, &expected, &inputs, &[one], Objective::RelErr, 2);
+        assert!(mean < max && max > 1e6);
+        assert!((rel - (1. + 0.5 + 1. / 3.) / 3.).abs() < 1e-6);
+        assert_eq!(evaluate(&f, &[], &[], &[one], Objective::MeanUlp, 4), 0.);
+    }
+
+    #[test]
+    fn any_thread_count() {
+        // the same sums whether split over threads or not
+        let inputs = uniform_inputs(5, 1.0..8.0, 100_000);
+        let expected: Vec<f32> = inputs.iter().map(|x| x.cbrt()).collect();
+        let errs: Vec<f64> = [1, 3, 64]
+            .map(|t| evaluate(&seed_only, &expected, &inputs, &[0x2a500000], Objective::MeanUlp, t))
+            .into();
+        assert!(errs[0] > 0. && errs.iter().all(|&e| e == errs[0]), "{errs:?}");
+    }
+}